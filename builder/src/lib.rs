@@ -1,165 +1,638 @@
 #![allow(dead_code)]
 
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast: syn::DeriveInput = syn::parse_macro_input!(input);
 
+    match derive_impl(&ast) {
+        std::result::Result::Ok(quote) => proc_macro::TokenStream::from(quote),
+        std::result::Result::Err(error) => proc_macro::TokenStream::from(error.to_compile_error()),
+    }
+}
+
+fn derive_impl(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let ident = &ast.ident;
-    let builder_name = format!("{ident}Builder");
+    let builder_name = format!("{}Builder", ident.unraw());
     let builder_ident = syn::Ident::new(&builder_name, ident.span());
 
-    let data_struct = match ast.data {
-        syn::Data::Struct(data) => data,
-        _ => { todo!() }
+    let attrs = container_attrs(&ast.attrs)?;
+    let constness = if attrs.is_const {
+        quote::quote!(const)
+    } else {
+        quote::quote!()
     };
 
-    let named = match data_struct.fields {
-        syn::Fields::Named(fields) => fields.named,
-        _ => { todo!() }
-    };
-
-    let inner_ty = |outer: &str, ty: &syn::Type| -> std::option::Option<syn::Type> {
-        if let syn::Type::Path(ref ty_path) = ty {
-            if ty_path.path.segments.len() == 1 && ty_path.path.segments[0].ident == outer {
-                if let syn::PathArguments::AngleBracketed(ref angle_bracketed) =
-                    ty_path.path.segments[0].arguments
-                {
-                    if let syn::GenericArgument::Type(ref unwrapped_inner_ty) =
-                        angle_bracketed.args[0]
-                    {
-                        return std::option::Option::Some(unwrapped_inner_ty.clone());
+    match ast.data {
+        syn::Data::Struct(ref data_struct) => {
+            let fields = builder_fields(&data_struct.fields)?;
+            check_const_fields(&attrs, &fields)?;
+
+            let builder_init = builder_init(&builder_ident, &fields);
+            let builder = builder_struct(
+                &builder_ident,
+                ident,
+                &quote::quote!(#ident),
+                &fields,
+                &attrs,
+            );
+
+            std::result::Result::Ok(quote::quote! {
+                impl #ident {
+                    pub #constness fn builder() -> #builder_ident {
+                        #builder_init
                     }
                 }
-            }
+
+                #builder
+            })
         }
+        syn::Data::Enum(ref data_enum) => {
+            let mut constructors = std::vec::Vec::new();
+            let mut builders = std::vec::Vec::new();
 
-        std::option::Option::None
-    };
+            for variant in &data_enum.variants {
+                if let syn::Fields::Unit = variant.fields {
+                    continue;
+                }
+
+                let variant_ident = &variant.ident;
+                let variant_builder_name =
+                    format!("{}{}Builder", ident.unraw(), variant_ident.unraw());
+                let variant_builder_ident =
+                    syn::Ident::new(&variant_builder_name, variant_ident.span());
+                let constructor_ident = snake_case_ident(variant_ident);
+
+                let fields = builder_fields(&variant.fields)?;
+                check_const_fields(&attrs, &fields)?;
 
-    let builder_attrs = |field: &syn::Field| -> std::option::Option<syn::Attribute> {
-        for attr in &field.attrs {
-            if attr.path().is_ident("builder") {
-                return std::option::Option::Some(attr.clone());
+                let builder_init = builder_init(&variant_builder_ident, &fields);
+
+                constructors.push(quote::quote! {
+                    pub #constness fn #constructor_ident() -> #variant_builder_ident {
+                        #builder_init
+                    }
+                });
+
+                builders.push(builder_struct(
+                    &variant_builder_ident,
+                    ident,
+                    &quote::quote!(#ident::#variant_ident),
+                    &fields,
+                    &attrs,
+                ));
             }
-        }
 
-        std::option::Option::None
-    };
+            std::result::Result::Ok(quote::quote! {
+                pub struct #builder_ident;
 
-    let nested_lit = |attr: &syn::Attribute| -> std::option::Option<String> {
-        let mut inert_ident: std::option::Option<String> = std::option::Option::None;
+                impl #builder_ident {
+                    #(#constructors)*
+                }
 
-        attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("each") {
-                let value = meta.value().unwrap();
-                let lit = value.parse::<syn::LitStr>().unwrap();
+                #(#builders)*
+            })
+        }
+        syn::Data::Union(_) => std::result::Result::Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "derive(Builder) expected struct or enum",
+        )),
+    }
+}
 
-                inert_ident = std::option::Option::Some(lit.value());
+/// Expression creating an empty builder, with every field unset.
+fn builder_init(builder_ident: &syn::Ident, fields: &[BuilderField]) -> proc_macro2::TokenStream {
+    let builder_default_fields = fields.iter().map(|field| {
+        let name = &field.name;
 
-                std::result::Result::Ok(())
-            } else {
-                std::result::Result::
-                Err(meta.error("expected `builder(each = \"...\")`"))
+        match field.each {
+            std::option::Option::Some(ref each) if !each.optional => {
+                quote::quote! { #name: std::default::Default::default() }
             }
-        }).ok()?;
+            _ => quote::quote! { #name: std::option::Option::None },
+        }
+    });
 
-        inert_ident
-    };
+    quote::quote! {
+        #builder_ident {
+            #(#builder_default_fields,)*
+        }
+    }
+}
 
-    let builder_fields = named.iter().map(|field| {
-        let name = &field.ident;
-        let ty = &field.ty;
+/// Builder struct and its setters for one set of fields. `built_ident` is the
+/// type returned by `build()` and `constructor` the path used to create it,
+/// which differ for enum variants.
+fn builder_struct(
+    builder_ident: &syn::Ident,
+    built_ident: &syn::Ident,
+    constructor: &proc_macro2::TokenStream,
+    fields: &[BuilderField],
+    attrs: &ContainerAttrs,
+) -> proc_macro2::TokenStream {
+    let builder_fields = fields.iter().map(|field| {
+        let name = &field.name;
+        let ty = field.ty;
+
+        if let std::option::Option::Some(ref each) = field.each {
+            let item = &each.item;
+
+            return match (each.boxed, each.optional) {
+                (true, true) => quote::quote! { #name: std::option::Option<std::vec::Vec<#item>> },
+                (true, false) => quote::quote! { #name: std::vec::Vec<#item> },
+                (false, _) => quote::quote! { #name: #ty },
+            };
+        }
 
-        if inner_ty("Option", ty).is_some() || builder_attrs(field).is_some() {
+        if option_inner_ty(ty).is_some() {
             return quote::quote! { #name: #ty };
         };
 
         quote::quote! { #name: std::option::Option<#ty> }
     });
 
-    let builder_default_fields = named.iter().map(|field| {
-        let name = &field.ident;
+    if attrs.is_const {
+        return const_builder_struct(
+            builder_ident,
+            built_ident,
+            constructor,
+            fields,
+            builder_fields,
+        );
+    }
+
+    let builder_methods = fields.iter().map(|field| {
+        let name = &field.name;
+        let ty = field.ty;
+
+        if let std::option::Option::Some(ref each) = field.each {
+            let inert_ident = &each.setter;
+            let item = &each.item;
+            let aliases = alias_methods(field, inert_ident, item, false);
+
+            let collection = if each.optional {
+                quote::quote! { self.#name.get_or_insert_with(std::default::Default::default) }
+            } else {
+                quote::quote! { &mut self.#name }
+            };
+
+            quote::quote! {
+                pub fn #inert_ident(&mut self, #inert_ident: #item) -> &mut Self {
+                    std::iter::Extend::extend(#collection, std::iter::once(#inert_ident));
+                    self
+                }
 
-        if builder_attrs(field).is_some() {
-            quote::quote! { #name: std::vec::Vec::new() }
+                #aliases
+            }
         } else {
-            quote::quote! { #name: None }
+            let inner_ty = option_inner_ty(ty).unwrap_or(ty);
+            let aliases = alias_methods(field, name, inner_ty, false);
+
+            quote::quote! {
+                pub fn #name(&mut self, #name: #inner_ty) -> &mut Self {
+                    self.#name = std::option::Option::Some(#name);
+                    self
+                }
+
+                #aliases
+            }
         }
     });
 
-    let builder_methods = named.iter().filter_map(|field| {
-        let name = &field.ident;
-        let ty = &field.ty;
+    let builder_build_fields = fields.iter().map(|field| {
+        let member = &field.member;
+        let name = &field.name;
+
+        match field.each {
+            std::option::Option::Some(ref each) if each.boxed && each.optional => {
+                return quote::quote! {
+                    #member: self.#name.clone().map(std::convert::Into::into)
+                };
+            }
+            std::option::Option::Some(ref each) if each.boxed => {
+                return quote::quote! { #member: self.#name.clone().into() };
+            }
+            std::option::Option::Some(_) => {
+                return quote::quote! { #member: self.#name.clone() };
+            }
+            std::option::Option::None => {}
+        }
 
-        if builder_attrs(field).is_none() {
-            let inner_ty = inner_ty("Option", ty).unwrap_or(ty.clone());
+        if option_inner_ty(field.ty).is_some() {
+            return quote::quote! { #member: self.#name.clone() };
+        };
 
-            return std::option::Option::Some(quote::quote! {
-                pub fn #name(&mut self, #name: #inner_ty) -> &mut Self {
-                    self.#name = Some(#name);
-                    self
-                }
-            });
-        } else {
-            let attr = builder_attrs(field).unwrap();
-            let inner_ty = inner_ty("Vec", ty).unwrap_or(ty.clone());
+        quote::quote! {
+            #member: self.#name.clone().ok_or(concat!(stringify!(#name), " is not set"))?
+        }
+    });
 
-            if let std::option::Option::Some(lit) = nested_lit(&attr) {
-                let inert_ident = syn::Ident::new(&lit, attr.span());
+    quote::quote! {
+        pub struct #builder_ident {
+            #(#builder_fields,)*
+        }
 
-                std::option::Option::Some(quote::quote! {
-                    pub fn #inert_ident(&mut self, #inert_ident: #inner_ty) -> &mut Self {
-                        self.#name.push(#inert_ident);
-                        self
-                    }
+        impl #builder_ident {
+            pub fn build(&mut self) -> std::result::Result<#built_ident, std::boxed::Box<dyn std::error::Error>> {
+                std::result::Result::Ok(#constructor {
+                    #(#builder_build_fields),*
                 })
-            } else {
-                std::option::Option::Some(
-                    syn::Error::new_spanned(&attr.meta, "expected `builder(each = \"...\")`")
-                        .to_compile_error(),
-                )
             }
+
+            #(#builder_methods)*
+        }
+    }
+}
+
+/// Owned-pattern variant of `builder_struct` for `#[builder(const)]`, where
+/// every setter and `build()` is a `const fn`. Missing fields panic, which is
+/// a compile error when the builder is evaluated in a const context.
+fn const_builder_struct(
+    builder_ident: &syn::Ident,
+    built_ident: &syn::Ident,
+    constructor: &proc_macro2::TokenStream,
+    fields: &[BuilderField],
+    builder_fields: impl Iterator<Item = proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let builder_methods = fields.iter().map(|field| {
+        let name = &field.name;
+        let inner_ty = option_inner_ty(field.ty).unwrap_or(field.ty);
+        let aliases = alias_methods(field, name, inner_ty, true);
+
+        quote::quote! {
+            pub const fn #name(mut self, #name: #inner_ty) -> Self {
+                self.#name = std::option::Option::Some(#name);
+                self
+            }
+
+            #aliases
         }
     });
 
-    let builder_build_fields = named.iter().map(|field| {
-        let name = &field.ident;
-        let ty = &field.ty;
+    let builder_build_fields = fields.iter().map(|field| {
+        let member = &field.member;
+        let name = &field.name;
 
-        if builder_attrs(field).is_some() || inner_ty("Option", ty).is_some() {
-            return quote::quote! { #name: self.#name.clone() };
+        if option_inner_ty(field.ty).is_some() {
+            return quote::quote! { #member: self.#name };
         };
 
         quote::quote! {
-            #name: self.#name.clone().ok_or(concat!(stringify!(#name), " is not set"))?
+            #member: match self.#name {
+                std::option::Option::Some(value) => value,
+                std::option::Option::None => panic!(concat!(stringify!(#name), " is not set")),
+            }
         }
     });
 
-    let quote = quote::quote! {
-        impl #ident {
-            pub fn builder() -> #builder_ident {
-                #builder_ident {
-                    #(#builder_default_fields,)*
-                }
-            }
+    // The setters drop the values they replace, which const fns cannot do for
+    // types that are not Copy. Requiring Copy on the impl reports such a field
+    // once, at its type, instead of as a destructor error at the derive.
+    let copy_bounds = fields.iter().map(|field| {
+        let ty = field.ty;
+
+        quote::quote_spanned! {ty.span()=>
+            #ty: std::marker::Copy
         }
+    });
 
+    quote::quote! {
         pub struct #builder_ident {
             #(#builder_fields,)*
         }
 
-        impl #builder_ident {
-            pub fn build(&mut self) -> std::result::Result<#ident, std::boxed::Box<dyn std::error::Error>> {
-                Ok(#ident {
+        impl #builder_ident
+        where
+            #(#copy_bounds,)*
+        {
+            pub const fn build(self) -> #built_ident {
+                #constructor {
                     #(#builder_build_fields),*
-                })
+                }
             }
 
             #(#builder_methods)*
         }
+    }
+}
+
+/// Deprecated setters under the field's former names, forwarding to `setter`.
+fn alias_methods(
+    field: &BuilderField,
+    setter: &syn::Ident,
+    ty: &syn::Type,
+    is_const: bool,
+) -> proc_macro2::TokenStream {
+    let methods = field.aliases.iter().map(|alias| {
+        let note = format!("use `{setter}` instead");
+
+        if is_const {
+            quote::quote! {
+                #[deprecated(note = #note)]
+                pub const fn #alias(self, #setter: #ty) -> Self {
+                    self.#setter(#setter)
+                }
+            }
+        } else {
+            quote::quote! {
+                #[deprecated(note = #note)]
+                pub fn #alias(&mut self, #setter: #ty) -> &mut Self {
+                    self.#setter(#setter)
+                }
+            }
+        }
+    });
+
+    quote::quote! { #(#methods)* }
+}
+
+/// Converts a variant name such as `PostForm` into a method name `post_form`.
+/// Names that are keywords become raw identifiers, except for the keywords
+/// that cannot be raw, which get a trailing underscore: `Crate` gives
+/// `crate_`.
+fn snake_case_ident(ident: &syn::Ident) -> syn::Ident {
+    let mut name = String::new();
+
+    for (index, ch) in ident.unraw().to_string().chars().enumerate() {
+        if ch.is_uppercase() {
+            if index != 0 {
+                name.push('_');
+            }
+            name.extend(ch.to_lowercase());
+        } else {
+            name.push(ch);
+        }
+    }
+
+    match name.as_str() {
+        "crate" | "self" | "super" => syn::Ident::new(&format!("{name}_"), ident.span()),
+        _ => match syn::parse_str::<syn::Ident>(&name) {
+            std::result::Result::Ok(_) => syn::Ident::new(&name, ident.span()),
+            std::result::Result::Err(_) => syn::Ident::new_raw(&name, ident.span()),
+        },
+    }
+}
+
+/// A field of the input as seen by the builder: `member` is how the field is
+/// initialized in the built value, `name` is the builder field and setter.
+struct BuilderField<'a> {
+    member: syn::Member,
+    name: syn::Ident,
+    ty: &'a syn::Type,
+    each: std::option::Option<Each>,
+    aliases: std::vec::Vec<syn::Ident>,
+}
+
+/// A field filled one element at a time through `#[builder(each = "...")]`.
+///
+/// `optional` fields are `Option<collection>`, left `None` until the first
+/// element is added. `boxed` fields are `Box<[T]>`, collected into a `Vec<T>`
+/// and converted on `build()`.
+struct Each {
+    setter: syn::Ident,
+    item: syn::Type,
+    optional: bool,
+    boxed: bool,
+}
+
+fn builder_fields(fields: &syn::Fields) -> syn::Result<std::vec::Vec<BuilderField<'_>>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let attrs = builder_attrs(field)?;
+
+            let member = match field.ident {
+                std::option::Option::Some(ref ident) => syn::Member::Named(ident.clone()),
+                std::option::Option::None => syn::Member::Unnamed(syn::Index {
+                    index: index as u32,
+                    span: field.ty.span(),
+                }),
+            };
+
+            let name = match (attrs.name, &field.ident) {
+                (std::option::Option::Some(lit), _) => lit.parse::<syn::Ident>()?,
+                (std::option::Option::None, std::option::Option::Some(ident)) => ident.clone(),
+                (std::option::Option::None, std::option::Option::None) => {
+                    syn::Ident::new(&format!("_{index}"), field.ty.span())
+                }
+            };
+
+            let each = match attrs.each {
+                std::option::Option::Some(lit) => {
+                    std::option::Option::Some(each(&field.ty, &lit, attrs.item.as_ref())?)
+                }
+                std::option::Option::None => std::option::Option::None,
+            };
+
+            let aliases = attrs
+                .aliases
+                .iter()
+                .map(|lit| lit.parse())
+                .collect::<syn::Result<_>>()?;
+
+            std::result::Result::Ok(BuilderField {
+                member,
+                name,
+                ty: &field.ty,
+                each,
+                aliases,
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    is_const: bool,
+}
+
+fn container_attrs(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container = ContainerAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("const") {
+                container.is_const = true;
+            } else {
+                return std::result::Result::Err(syn::Error::new_spanned(
+                    &attr.meta,
+                    "expected `builder(const)`",
+                ));
+            }
+
+            std::result::Result::Ok(())
+        })?;
+    }
+
+    std::result::Result::Ok(container)
+}
+
+/// Repeated fields need a growable collection, which const builders cannot
+/// allocate.
+fn check_const_fields(attrs: &ContainerAttrs, fields: &[BuilderField]) -> syn::Result<()> {
+    if !attrs.is_const {
+        return std::result::Result::Ok(());
+    }
+
+    for field in fields {
+        if let std::option::Option::Some(ref each) = field.each {
+            return std::result::Result::Err(syn::Error::new(
+                each.setter.span(),
+                "`builder(each = \"...\")` is not supported by `builder(const)`",
+            ));
+        }
+    }
+
+    std::result::Result::Ok(())
+}
+
+#[derive(Default)]
+struct BuilderAttrs {
+    each: std::option::Option<syn::LitStr>,
+    name: std::option::Option<syn::LitStr>,
+    aliases: std::vec::Vec<syn::LitStr>,
+    item: std::option::Option<syn::LitStr>,
+}
+
+fn builder_attrs(field: &syn::Field) -> syn::Result<BuilderAttrs> {
+    let mut attrs = BuilderAttrs::default();
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("each") {
+                attrs.each = std::option::Option::Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("name") {
+                attrs.name = std::option::Option::Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("item") {
+                attrs.item = std::option::Option::Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("alias") {
+                attrs.aliases.push(meta.value()?.parse()?);
+            } else {
+                return std::result::Result::Err(syn::Error::new_spanned(
+                    &attr.meta,
+                    "expected `builder(each = \"...\")`",
+                ));
+            }
+
+            std::result::Result::Ok(())
+        })?;
+    }
+
+    std::result::Result::Ok(attrs)
+}
+
+/// Classifies a repeated field. The item type comes from `item = "..."` when
+/// given, which is needed for type aliases, and otherwise from the single
+/// generic argument of the collection.
+fn each(
+    ty: &syn::Type,
+    setter: &syn::LitStr,
+    item: std::option::Option<&syn::LitStr>,
+) -> syn::Result<Each> {
+    let setter = setter.parse()?;
+
+    let (collection, optional) = match option_inner_ty(ty) {
+        std::option::Option::Some(inner_ty) => (inner_ty, true),
+        std::option::Option::None => (ty, false),
+    };
+
+    let boxed_item = path_inner_ty(BOX, collection).and_then(|inner_ty| match inner_ty {
+        syn::Type::Slice(slice) => std::option::Option::Some(&*slice.elem),
+        _ => std::option::Option::None,
+    });
+
+    let item = match (item, boxed_item) {
+        (std::option::Option::Some(lit), _) => lit.parse()?,
+        (std::option::Option::None, std::option::Option::Some(boxed_item)) => boxed_item.clone(),
+        (std::option::Option::None, std::option::Option::None) => {
+            match collection_item_ty(collection) {
+                std::option::Option::Some(item) => item.clone(),
+                std::option::Option::None => {
+                    return std::result::Result::Err(syn::Error::new_spanned(
+                        ty,
+                        "cannot infer the item type, add `builder(item = \"...\")`",
+                    ))
+                }
+            }
+        }
     };
 
-    proc_macro::TokenStream::from(quote)
+    std::result::Result::Ok(Each {
+        setter,
+        item,
+        optional,
+        boxed: boxed_item.is_some(),
+    })
+}
+
+const OPTION: &[&[&str]] = &[&["std", "option", "Option"], &["core", "option", "Option"]];
+const BOX: &[&[&str]] = &[&["std", "boxed", "Box"], &["alloc", "boxed", "Box"]];
+
+fn option_inner_ty(ty: &syn::Type) -> std::option::Option<&syn::Type> {
+    path_inner_ty(OPTION, ty)
+}
+
+/// Unwraps `T` from `Outer<T>`, where `Outer` is written as any suffix of one
+/// of the `outer` paths: `Option`, `option::Option`, `::std::option::Option`.
+fn path_inner_ty<'a>(outer: &[&[&str]], ty: &'a syn::Type) -> std::option::Option<&'a syn::Type> {
+    let syn::Type::Path(ref ty_path) = ty else {
+        return std::option::Option::None;
+    };
+
+    if ty_path.qself.is_some() {
+        return std::option::Option::None;
+    }
+
+    let segments = &ty_path.path.segments;
+    let last = segments.last()?;
+
+    let matches = outer.iter().any(|path| {
+        path.len() >= segments.len()
+            && (ty_path.path.leading_colon.is_none() || path.len() == segments.len())
+            && path[path.len() - segments.len()..]
+                .iter()
+                .zip(segments.iter())
+                .all(|(expected, segment)| segment.ident == expected)
+    });
+
+    let mut prefix = segments.iter().take(segments.len() - 1);
+    if !matches || prefix.any(|segment| !segment.arguments.is_none()) {
+        return std::option::Option::None;
+    }
+
+    generic_ty_arg(last)
+}
+
+/// Item type of a collection such as `Vec<T>` or `std::collections::HashSet<T>`,
+/// taken from the only generic argument of its last path segment.
+fn collection_item_ty(ty: &syn::Type) -> std::option::Option<&syn::Type> {
+    match ty {
+        syn::Type::Path(ty_path) if ty_path.qself.is_none() => {
+            generic_ty_arg(ty_path.path.segments.last()?)
+        }
+        _ => std::option::Option::None,
+    }
+}
+
+fn generic_ty_arg(segment: &syn::PathSegment) -> std::option::Option<&syn::Type> {
+    if let syn::PathArguments::AngleBracketed(ref angle_bracketed) = segment.arguments {
+        if angle_bracketed.args.len() == 1 {
+            if let syn::GenericArgument::Type(ref unwrapped_inner_ty) = angle_bracketed.args[0] {
+                return std::option::Option::Some(unwrapped_inner_ty);
+            }
+        }
+    }
+
+    std::option::Option::None
 }
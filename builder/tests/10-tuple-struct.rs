@@ -0,0 +1,47 @@
+// Tuple structs have no field names to derive setter names from. Each
+// positional field may be given one with #[builder(name = "...")]; fields
+// without it fall back to `_0`, `_1` and so on.
+//
+// Apart from naming, positional fields follow the same rules as named ones:
+// plain fields are required, Option fields are optional, and fields with
+// #[builder(each = "...")] are filled one element at a time.
+//
+//     impl RgbBuilder {
+//         pub fn red(&mut self, red: u8) -> &mut Self {...}
+//         pub fn green(&mut self, green: u8) -> &mut Self {...}
+//         pub fn _2(&mut self, _2: u8) -> &mut Self {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Rgb(
+    #[builder(name = "red")] u8,
+    #[builder(name = "green")] u8,
+    u8,
+);
+
+#[derive(Builder)]
+pub struct Command(
+    #[builder(name = "executable")] String,
+    #[builder(name = "args", each = "arg")] Vec<String>,
+    #[builder(name = "current_dir")] Option<String>,
+);
+
+fn main() {
+    let rgb = Rgb::builder().red(255).green(128)._2(0).build().unwrap();
+    assert_eq!((rgb.0, rgb.1, rgb.2), (255, 128, 0));
+
+    assert!(Rgb::builder().red(255).build().is_err());
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.0, "cargo");
+    assert_eq!(command.1, vec!["build", "--release"]);
+    assert!(command.2.is_none());
+}
@@ -0,0 +1,92 @@
+// An enum gets one builder per variant with fields. The `{Enum}Builder` type
+// acts as the entry point, with one constructor per variant named after the
+// variant in snake case, returning a `{Enum}{Variant}Builder`:
+//
+//     impl RequestBuilder {
+//         pub fn get() -> RequestGetBuilder {...}
+//         pub fn post_form() -> RequestPostFormBuilder {...}
+//     }
+//
+// Variants whose snake case name is a keyword get a raw identifier, such as
+// `r#type()`, or a trailing underscore for the keywords that cannot be raw,
+// such as `crate_()`. Raw variant names lose their `r#` in builder names, so
+// `r#Loop` gets a `RequestLoopBuilder`.
+//
+// Each variant builder follows the same field rules as a struct builder, and
+// its build() method returns the enum.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Request {
+    Get {
+        url: String,
+        timeout: Option<u64>,
+    },
+    PostForm {
+        url: String,
+        #[builder(each = "field")]
+        fields: Vec<(String, String)>,
+    },
+    Raw(#[builder(name = "bytes")] Vec<u8>),
+    Type(u8),
+    Crate {
+        name: String,
+    },
+    r#Loop {
+        count: u8,
+    },
+    Ping,
+}
+
+fn main() {
+    let get = RequestBuilder::get()
+        .url("/index.html".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        get,
+        Request::Get {
+            url: "/index.html".to_owned(),
+            timeout: None,
+        }
+    );
+
+    let post = RequestBuilder::post_form()
+        .url("/login".to_owned())
+        .field(("user".to_owned(), "root".to_owned()))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        post,
+        Request::PostForm {
+            url: "/login".to_owned(),
+            fields: vec![("user".to_owned(), "root".to_owned())],
+        }
+    );
+
+    let raw = RequestBuilder::raw().bytes(vec![1, 2, 3]).build().unwrap();
+    assert_eq!(raw, Request::Raw(vec![1, 2, 3]));
+
+    let r#type = RequestBuilder::r#type()._0(1).build().unwrap();
+    assert_eq!(r#type, Request::Type(1));
+
+    let krate = RequestBuilder::crate_()
+        .name("std".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        krate,
+        Request::Crate {
+            name: "std".to_owned(),
+        }
+    );
+
+    let mut r#loop: RequestLoopBuilder = RequestBuilder::r#loop();
+    assert_eq!(r#loop.count(3).build().unwrap(), Request::r#Loop { count: 3 });
+
+    assert!(RequestBuilder::get().build().is_err());
+    assert_ne!(raw, Request::Ping);
+}
@@ -0,0 +1,51 @@
+// With #[builder(const)] on the struct, the builder is usable in const
+// contexts such as static configuration tables. All fields must be Copy.
+//
+// The generated builder uses the owned pattern so that every setter and the
+// build() method can be a const fn:
+//
+//     impl ConfigBuilder {
+//         pub const fn port(mut self, port: u16) -> Self {...}
+//         pub const fn build(self) -> Config {...}
+//     }
+//
+// Since a const fn cannot return an allocated error, build() panics when a
+// required field is missing. In a const context this becomes a compile error.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Config {
+    name: &'static str,
+    port: u16,
+    timeout: Option<u32>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub enum Limit {
+    Fixed(#[builder(name = "value")] u32),
+    Range { min: u32, max: u32 },
+}
+
+static CONFIGS: [Config; 2] = [
+    Config::builder().name("http").port(80).build(),
+    Config::builder()
+        .name("https")
+        .port(443)
+        .timeout(30)
+        .build(),
+];
+
+const LIMIT: Limit = LimitBuilder::range().min(1).max(8).build();
+
+fn main() {
+    assert_eq!(CONFIGS[0].name, "http");
+    assert_eq!(CONFIGS[0].port, 80);
+    assert_eq!(CONFIGS[0].timeout, None);
+    assert_eq!(CONFIGS[1].timeout, Some(30));
+
+    assert_eq!(LIMIT, Limit::Range { min: 1, max: 8 });
+    assert_eq!(LimitBuilder::fixed().value(3).build(), Limit::Fixed(3));
+}
@@ -0,0 +1,17 @@
+// A const builder reports a missing required field at compile time when it is
+// evaluated in a const context.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Config {
+    name: &'static str,
+    port: u16,
+}
+
+const CONFIG: Config = Config::builder().name("http").build();
+
+fn main() {
+    let _ = CONFIG.port;
+}
@@ -0,0 +1,46 @@
+// Renaming a field renames its setter, which breaks every caller at once. To
+// stage the migration, #[builder(alias = "...")] keeps the old setter name
+// around as a deprecated method forwarding to the new one:
+//
+//     impl CommandBuilder {
+//         pub fn program(&mut self, program: String) -> &mut Self {...}
+//
+//         #[deprecated(note = "use `program` instead")]
+//         pub fn executable(&mut self, program: String) -> &mut Self {
+//             self.program(program)
+//         }
+//     }
+//
+// The attribute may be repeated for fields renamed more than once. On a field
+// with #[builder(each = "...")] the alias forwards to the one-at-a-time setter.
+
+#![allow(deprecated)]
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(alias = "executable", alias = "exe")]
+    program: String,
+    #[builder(each = "arg", alias = "argument")]
+    args: Vec<String>,
+    #[builder(alias = "cwd")]
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .argument("build".to_owned())
+        .arg("--release".to_owned())
+        .cwd("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.program, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = Command::builder().exe("rustc".to_owned()).build().unwrap();
+    assert_eq!(command.program, "rustc");
+}
@@ -0,0 +1,16 @@
+// Calling a setter through its alias triggers the deprecation lint, pointing
+// callers at the new name.
+
+#![deny(deprecated)]
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(alias = "executable")]
+    program: String,
+}
+
+fn main() {
+    let _ = Command::builder().executable("cargo".to_owned()).build();
+}
@@ -0,0 +1,66 @@
+// Fields with #[builder(each = "...")] are not limited to a bare `Vec<T>`.
+//
+// Standard types are recognized by their fully qualified paths as well, so
+// `std::vec::Vec<T>` and `std::option::Option<T>` behave like `Vec<T>` and
+// `Option<T>`. Any collection implementing Default and Extend works, with the
+// item type taken from its generic argument.
+//
+// An `Option<collection>` field stays `None` until the first element is
+// added, and a `Box<[T]>` field is collected into a `Vec<T>` and converted by
+// build().
+//
+// For type aliases the item type is not visible from the field type, so it is
+// given explicitly with #[builder(each = "...", item = "...")].
+
+use derive_builder::Builder;
+use std::collections::BTreeSet;
+
+type Env = Vec<(String, String)>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "env", item = "(String, String)")]
+    env: Env,
+    #[builder(each = "feature")]
+    features: Option<Vec<String>>,
+    #[builder(each = "input")]
+    inputs: Box<[u8]>,
+    #[builder(each = "tag")]
+    tags: BTreeSet<&'static str>,
+    current_dir: std::option::Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env(("RUSTFLAGS".to_owned(), "-Dwarnings".to_owned()))
+        .input(1)
+        .input(2)
+        .tag("b")
+        .tag("a")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env.len(), 1);
+    assert_eq!(command.features, None);
+    assert_eq!(&*command.inputs, &[1, 2]);
+    assert_eq!(command.tags.into_iter().collect::<Vec<_>>(), vec!["a", "b"]);
+    assert!(command.current_dir.is_none());
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .feature("std".to_owned())
+        .feature("derive".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.features, Some(vec!["std".to_owned(), "derive".to_owned()]));
+    assert!(command.inputs.is_empty());
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
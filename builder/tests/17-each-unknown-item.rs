@@ -0,0 +1,15 @@
+// The item type of a type alias cannot be inferred from the field type alone.
+// The error should point at the type and suggest the `item` escape hatch.
+
+use derive_builder::Builder;
+
+type Args = Vec<String>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Args,
+}
+
+fn main() {}
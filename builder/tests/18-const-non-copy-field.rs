@@ -0,0 +1,13 @@
+// All fields of a const builder must be Copy. A field that is not is reported
+// at its type rather than at the derive.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Config {
+    name: String,
+    port: u16,
+}
+
+fn main() {}
@@ -10,4 +10,13 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-tuple-struct.rs");
+    t.pass("tests/11-enum.rs");
+    t.pass("tests/12-const-builder.rs");
+    t.compile_fail("tests/13-const-missing-field.rs");
+    t.pass("tests/14-deprecated-alias.rs");
+    t.compile_fail("tests/15-deprecated-alias-warning.rs");
+    t.pass("tests/16-each-collections.rs");
+    t.compile_fail("tests/17-each-unknown-item.rs");
+    t.compile_fail("tests/18-const-non-copy-field.rs");
 }
@@ -0,0 +1,2150 @@
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast: syn::DeriveInput = syn::parse_macro_input!(input);
+
+    // dbg!(&ast);
+
+    let struct_ident = &ast.ident;
+    let container_attrs = container_attrs(&ast.attrs);
+    let generic_types = &ast
+        .generics
+        .type_params()
+        .map(|t| &t.ident)
+        .collect::<Vec<&syn::Ident>>();
+
+    let mut flattened = None;
+    // Bounds of a container template, which replace the fields' own in the
+    // `Debug` impl since it prints only what the template refers to.
+    let mut template_inferred = None;
+
+    let (body, inspect, fields) = match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            let fields = debug_fields(&data_struct.fields, &container_attrs);
+            let accesses = data_struct
+                .fields
+                .members()
+                .map(|member| quote::quote! { &self.#member })
+                .collect::<Vec<_>>();
+
+            let name = display_name(struct_ident, container_attrs.rename.as_ref());
+
+            if let syn::Fields::Named(_) = data_struct.fields {
+                flattened = Some(flattened_fields(&fields, &accesses));
+            }
+
+            let body = match (&container_attrs.transparent, &container_attrs.template) {
+                (Some(transparent), template) => {
+                    if let Some(template) = template {
+                        proc_macro_error::emit_error!(
+                            template.format.span(),
+                            "`debug(fmt = \"...\")` cannot be combined with `debug(transparent)`"
+                        );
+                    }
+
+                    transparent_debug(transparent, &fields, &accesses)
+                }
+                (None, Some(template)) => {
+                    let value = match expand_template(template, &data_struct.fields) {
+                        Ok(template) => {
+                            template_inferred = Some(template_bounds(&template, generic_types));
+                            template_value(&template)
+                        }
+                        Err(error) => emit_errors(error),
+                    };
+
+                    quote::quote! {
+                        ::core::fmt::Display::fmt(#value, f)
+                    }
+                }
+                (None, None) => fields_debug(&name, &data_struct.fields, &fields, &accesses),
+            };
+
+            let inspect = inspect_statements(&fields, &accesses);
+            let inspect = quote::quote! { #(#inspect)* };
+
+            (body, inspect, fields)
+        }
+        syn::Data::Enum(data_enum) => {
+            if let Some(ref transparent) = container_attrs.transparent {
+                proc_macro_error::emit_error!(
+                    transparent.span(),
+                    "`debug(transparent)` expected struct with a single field"
+                );
+            }
+
+            if let Some(ref rename) = container_attrs.rename {
+                proc_macro_error::emit_error!(
+                    rename.span(),
+                    "enum names are not printed, rename the variants instead"
+                );
+            }
+
+            if let Some(ref template) = container_attrs.template {
+                proc_macro_error::emit_error!(
+                    template.format.span(),
+                    "`debug(fmt = \"...\")` expected struct"
+                );
+            }
+
+            let mut fields = Vec::new();
+
+            let arms = data_enum
+                .variants
+                .iter()
+                .map(|variant: &syn::Variant| {
+                    let variant_ident = &variant.ident;
+                    let variant_attrs = variant_attrs(&variant.attrs);
+                    let name = display_name(variant_ident, variant_attrs.rename.as_ref());
+                    let variant_fields = debug_fields(&variant.fields, &container_attrs);
+
+                    for field in &variant_fields {
+                        if let Some(ref template) = field.attrs.template {
+                            proc_macro_error::emit_error!(
+                                template.format.span(),
+                                "`debug(fmt = \"...\")` expected struct field"
+                            );
+                        }
+                    }
+                    let members = variant.fields.members();
+                    let bindings = (0..variant.fields.len())
+                        .map(|index| quote::format_ident!("__self_{}", index))
+                        .collect::<Vec<_>>();
+
+                    let accesses = bindings
+                        .iter()
+                        .map(|binding| quote::quote! { #binding })
+                        .collect::<Vec<_>>();
+                    let debug = fields_debug(&name, &variant.fields, &variant_fields, &accesses);
+                    let inspect = inspect_statements(&variant_fields, &accesses);
+                    fields.extend(variant_fields);
+
+                    let pattern = quote::quote! {
+                        Self::#variant_ident { #(#members: #bindings),* }
+                    };
+
+                    (
+                        quote::quote! { #pattern => #debug, },
+                        quote::quote! { #pattern => { #(#inspect)* } },
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let (arms, inspect_arms): (Vec<_>, Vec<_>) = arms.into_iter().unzip();
+
+            let (body, inspect) = if data_enum.variants.is_empty() {
+                (
+                    quote::quote! { match *self {} },
+                    quote::quote! { match *self {} },
+                )
+            } else {
+                (
+                    quote::quote! { match self { #(#arms)* } },
+                    quote::quote! { match self { #(#inspect_arms)* } },
+                )
+            };
+
+            (body, inspect, fields)
+        }
+        // Which field of a union is initialized is unknown, so none of them
+        // can be read safely.
+        syn::Data::Union(_) => {
+            if let Some(ref transparent) = container_attrs.transparent {
+                proc_macro_error::emit_error!(
+                    transparent.span(),
+                    "`debug(transparent)` expected struct with a single field"
+                );
+            }
+
+            if let Some(ref template) = container_attrs.template {
+                proc_macro_error::emit_error!(
+                    template.format.span(),
+                    "`debug(fmt = \"...\")` expected struct"
+                );
+            }
+
+            let name = display_name(struct_ident, container_attrs.rename.as_ref());
+            let body = quote::quote! {
+                f.debug_struct(#name).finish_non_exhaustive()
+            };
+
+            (body, quote::quote! {}, Vec::new())
+        }
+    };
+
+    if let Some(ref except) = container_attrs.redact_all_except {
+        for ident in except {
+            if !fields
+                .iter()
+                .any(|field| field.field.ident.as_ref() == Some(ident))
+            {
+                proc_macro_error::emit_error!(ident.span(), "no field named `{}`", ident);
+            }
+        }
+    }
+
+    let mut inferred = Vec::new();
+    for field in &fields {
+        for bound in field_bounds(field, generic_types) {
+            if !inferred.contains(&bound) {
+                inferred.push(bound);
+            }
+        }
+    }
+
+    let debug_generics = add_impl_generics_bounds(
+        ast.generics.clone(),
+        template_inferred.as_deref().unwrap_or(&inferred),
+        container_attrs.bounds.as_deref(),
+    );
+    let (debug_impl_generics, _, debug_where_clause) = debug_generics.split_for_impl();
+
+    let generics = add_impl_generics_bounds(
+        ast.generics.clone(),
+        &inferred,
+        container_attrs.bounds.as_deref(),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let flattened = flattened.map(|flattened| {
+        quote::quote! {
+            impl #impl_generics ::derive_debug::__private::Flatten for #struct_ident #ty_generics #where_clause {
+                fn debug_fields(&self, __debug: &mut ::core::fmt::DebugStruct<'_, '_>) -> bool {
+                    #flattened
+                }
+            }
+        }
+    });
+
+    let quote = quote::quote! {
+         impl #debug_impl_generics ::core::fmt::Debug for #struct_ident #ty_generics #debug_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+
+        impl #impl_generics ::derive_debug::Inspect for #struct_ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn visit(&self, v: &mut dyn ::derive_debug::FieldVisitor) {
+                #inspect
+            }
+        }
+
+        #flattened
+    };
+
+    proc_macro::TokenStream::from(quote)
+}
+
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast: syn::DeriveInput = syn::parse_macro_input!(input);
+
+    let ident = &ast.ident;
+    let generic_types = &ast
+        .generics
+        .type_params()
+        .map(|t| &t.ident)
+        .collect::<Vec<&syn::Ident>>();
+    let mut inferred = Vec::new();
+
+    let body = match &ast.data {
+        syn::Data::Struct(data_struct) => {
+            let accesses = data_struct
+                .fields
+                .members()
+                .map(|member| quote::quote! { &self.#member })
+                .collect::<Vec<_>>();
+
+            match display_attr(&ast.attrs, ident) {
+                Some(template) => display_template(
+                    &template,
+                    &data_struct.fields,
+                    &accesses,
+                    generic_types,
+                    &mut inferred,
+                ),
+                None => quote::quote! { ::core::result::Result::Ok(()) },
+            }
+        }
+        syn::Data::Enum(data_enum) => {
+            if let Some(attr) = ast
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("display"))
+            {
+                proc_macro_error::emit_error!(
+                    attr.meta.span(),
+                    "`display(\"...\")` expected on each variant of an enum"
+                );
+            }
+
+            let arms = data_enum
+                .variants
+                .iter()
+                .map(|variant: &syn::Variant| {
+                    let variant_ident = &variant.ident;
+                    let members = variant.fields.members();
+                    let bindings = (0..variant.fields.len())
+                        .map(|index| quote::format_ident!("__self_{}", index))
+                        .collect::<Vec<_>>();
+
+                    let accesses = bindings
+                        .iter()
+                        .map(|binding| quote::quote! { #binding })
+                        .collect::<Vec<_>>();
+                    let display = match display_attr(&variant.attrs, variant_ident) {
+                        Some(template) => display_template(
+                            &template,
+                            &variant.fields,
+                            &accesses,
+                            generic_types,
+                            &mut inferred,
+                        ),
+                        None => quote::quote! { ::core::result::Result::Ok(()) },
+                    };
+
+                    quote::quote! {
+                        #[allow(unused_variables)]
+                        Self::#variant_ident { #(#members: #bindings),* } => #display,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if data_enum.variants.is_empty() {
+                quote::quote! { match *self {} }
+            } else {
+                quote::quote! { match self { #(#arms)* } }
+            }
+        }
+        syn::Data::Union(_) => {
+            proc_macro_error::abort_call_site!("derive(CustomDisplay) expected struct or enum")
+        }
+    };
+
+    let generics = add_impl_generics_bounds(ast.generics.clone(), &inferred, None);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let quote = quote::quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    };
+
+    proc_macro::TokenStream::from(quote)
+}
+
+/// Formats one struct or enum variant printed as `name`, where `accesses`
+/// holds a reference expression to each of its `fields` in order.
+///
+/// Skipped fields are left out, and the output then ends with `..` through
+/// `finish_non_exhaustive()` so that readers can tell it is partial.
+fn fields_debug(
+    name: &syn::LitStr,
+    fields: &syn::Fields,
+    debug_fields: &[DebugField],
+    accesses: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let builder = match fields {
+        syn::Fields::Named(_) => quote::quote! { f.debug_struct(#name) },
+        syn::Fields::Unnamed(_) => quote::quote! { f.debug_tuple(#name) },
+        syn::Fields::Unit => {
+            return quote::quote! {
+                f.write_str(#name)
+            }
+        }
+    };
+
+    let FieldStatements {
+        statements,
+        skipped,
+        dynamic,
+    } = field_statements(debug_fields, accesses);
+
+    let finish = if skipped {
+        quote::quote! { __debug.finish_non_exhaustive() }
+    } else if dynamic {
+        quote::quote! {
+            if __non_exhaustive {
+                __debug.finish_non_exhaustive()
+            } else {
+                __debug.finish()
+            }
+        }
+    } else {
+        quote::quote! { __debug.finish() }
+    };
+
+    let non_exhaustive = if dynamic {
+        quote::quote! { let mut __non_exhaustive = false; }
+    } else {
+        quote::quote! {}
+    };
+
+    quote::quote! {
+        {
+            let __debug = &mut #builder;
+            #non_exhaustive
+            #(#statements)*
+            #finish
+        }
+    }
+}
+
+/// Body of the hidden `Flatten` implementation through which a struct's fields
+/// are inlined by `#[debug(flatten)]` into the `DebugStruct` of another one.
+/// Returns whether any field was left out.
+fn flattened_fields(
+    debug_fields: &[DebugField],
+    accesses: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let FieldStatements {
+        statements,
+        skipped,
+        dynamic,
+    } = field_statements(debug_fields, accesses);
+
+    if dynamic {
+        quote::quote! {
+            let mut __non_exhaustive = false;
+            #(#statements)*
+            #skipped || __non_exhaustive
+        }
+    } else {
+        quote::quote! {
+            #(#statements)*
+            #skipped
+        }
+    }
+}
+
+/// Prints a newtype as its only field, as `#[debug(transparent)]` requests.
+fn transparent_debug(
+    transparent: &syn::Path,
+    debug_fields: &[DebugField],
+    accesses: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let mut printed = debug_fields
+        .iter()
+        .zip(accesses)
+        .filter(|(field, _)| !field.attrs.skip);
+
+    match (printed.next(), printed.next()) {
+        (Some((field, access)), None) => {
+            let value = field_value(field, access);
+
+            quote::quote! {
+                ::core::fmt::Debug::fmt(#value, f)
+            }
+        }
+        _ => proc_macro_error::abort!(
+            transparent.span(),
+            "`debug(transparent)` expected struct with a single field"
+        ),
+    }
+}
+
+/// Statements passing each field printed by `Debug` to the visitor `v` of
+/// `Inspect::visit`, named and formatted the same way.
+fn inspect_statements(
+    debug_fields: &[DebugField],
+    accesses: &[proc_macro2::TokenStream],
+) -> Vec<proc_macro2::TokenStream> {
+    debug_fields
+        .iter()
+        .zip(accesses)
+        .enumerate()
+        .filter(|(_, (field, _))| !field.attrs.skip)
+        .map(|(index, (field, access))| {
+            let statement = if field.attrs.flatten.is_some() {
+                let ty = &field.field.ty;
+
+                let visit = quote::quote_spanned! {ty.span()=>
+                    ::derive_debug::Inspect::visit(__field, v);
+                };
+
+                quote::quote! {
+                    let __field = #access;
+                    #visit
+                }
+            } else {
+                let value = field_value(field, access);
+                let field_name = match field.field.ident {
+                    Some(ref field_ident) => display_name(field_ident, field.attrs.rename.as_ref()),
+                    None => syn::LitStr::new(&index.to_string(), field.field.ty.span()),
+                };
+
+                quote::quote! {
+                    v.visit_field(#field_name, #value);
+                }
+            };
+
+            match field.attrs.skip_if {
+                Some(ref skip_if) => quote::quote! {
+                    if !#skip_if(#access) {
+                        #statement
+                    }
+                },
+                None => statement,
+            }
+        })
+        .collect()
+}
+
+/// Statements adding each field to a `__debug` builder. `skipped` tells that
+/// some field is always left out, `dynamic` that fields may be left out at
+/// runtime, which is then recorded in `__non_exhaustive`.
+struct FieldStatements {
+    statements: Vec<proc_macro2::TokenStream>,
+    skipped: bool,
+    dynamic: bool,
+}
+
+fn field_statements(
+    debug_fields: &[DebugField],
+    accesses: &[proc_macro2::TokenStream],
+) -> FieldStatements {
+    let mut skipped = false;
+    let mut dynamic = false;
+
+    let statements = debug_fields
+        .iter()
+        .zip(accesses)
+        .filter_map(|(field, access)| {
+            if field.attrs.skip {
+                skipped = true;
+                return None;
+            }
+
+            let statement = if field.attrs.flatten.is_some() {
+                dynamic = true;
+
+                // Spanned to the field type, which is where a type without
+                // named fields or without the derive is reported.
+                let ty = &field.field.ty;
+                let debug_fields = quote::quote_spanned! {ty.span()=>
+                    ::derive_debug::__private::Flatten::debug_fields(__field, __debug)
+                };
+
+                quote::quote! {
+                    let __field = #access;
+                    if #debug_fields {
+                        __non_exhaustive = true;
+                    }
+                }
+            } else {
+                let value = field_value(field, access);
+
+                match field.field.ident {
+                    Some(ref field_ident) => {
+                        let field_name = display_name(field_ident, field.attrs.rename.as_ref());
+
+                        quote::quote! {
+                            __debug.field(#field_name, #value);
+                        }
+                    }
+                    None => quote::quote! {
+                        __debug.field(#value);
+                    },
+                }
+            };
+
+            match field.attrs.skip_if {
+                Some(ref skip_if) => {
+                    dynamic = true;
+
+                    Some(quote::quote! {
+                        if #skip_if(#access) {
+                            __non_exhaustive = true;
+                        } else {
+                            #statement
+                        }
+                    })
+                }
+                None => Some(statement),
+            }
+        })
+        .collect();
+
+    FieldStatements {
+        statements,
+        skipped,
+        dynamic,
+    }
+}
+
+/// Name printed for a type, variant or field: the `rename` given for it, or
+/// its identifier without any `r#` prefix.
+fn display_name(ident: &syn::Ident, rename: Option<&syn::LitStr>) -> syn::LitStr {
+    match rename {
+        Some(rename) => rename.clone(),
+        None => syn::LitStr::new(&ident.unraw().to_string(), ident.span()),
+    }
+}
+
+/// Expression for the value printed for one field, given a reference to it.
+fn field_value(field: &DebugField, access: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some(ref redact) = field.attrs.redact {
+        return redacted(redact, access);
+    }
+
+    if let Some(ref with) = field.attrs.with {
+        return debug_with(with, access);
+    }
+
+    if let Some(ref template) = field.attrs.template {
+        return template_value(template);
+    }
+
+    if let Some((_, ref preset)) = field.attrs.preset {
+        return preset_value(preset, &field.field.ty, access);
+    }
+
+    if field.attrs.is_collection() {
+        let each = field.attrs.each.as_ref().map(|(_, each)| each);
+        let collection = field.attrs.collection(&field.field.ty);
+        let sorted = field.attrs.sorted.as_ref();
+        return collection_value(each, collection, sorted, &field.field.ty, access);
+    }
+
+    match field.attrs.format {
+        Some(ref lit_str) => quote::quote! { &::core::format_args!(#lit_str, #access) },
+        None => quote::quote! { &#access },
+    }
+}
+
+/// Adapter printing an `IntoIterator` field item by item, as a list, a set or
+/// a map. `#[debug(each = "...")]` and `#[debug(each_with = "...")]` format
+/// every item, or every value of a map. `#[debug(sorted)]` orders the entries
+/// by key.
+fn collection_value(
+    each: Option<&Each>,
+    collection: Collection,
+    sorted: Option<&syn::Path>,
+    ty: &syn::Type,
+    access: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    // `&&[T]` is not `IntoIterator`, so references held by the field are
+    // iterated through directly.
+    let iterable = match ty {
+        syn::Type::Reference(_) => quote::quote! { *#access },
+        _ => quote::quote! { #access },
+    };
+
+    let item = |item: proc_macro2::TokenStream| match each {
+        Some(Each::Format(lit_str)) => quote::quote! { &::core::format_args!(#lit_str, #item) },
+        Some(Each::With(with)) => debug_with(with, &item),
+        None => item,
+    };
+
+    let (builder, pattern, key_pattern, key, entry) = if collection == Collection::Map {
+        let value = item(quote::quote! { __value });
+
+        (
+            quote::quote! { f.debug_map() },
+            // Spanned so that a type iterating something other than pairs is
+            // reported at the field.
+            quote::quote_spanned! {ty.span()=> (__key, __value) },
+            quote::quote! { (__key, _) },
+            quote::quote! { __key },
+            quote::quote! { __entries.entry(__key, #value); },
+        )
+    } else {
+        let value = item(quote::quote! { __item });
+        let builder = match collection {
+            Collection::Set => quote::quote! { f.debug_set() },
+            _ => quote::quote! { f.debug_list() },
+        };
+
+        (
+            builder,
+            quote::quote! { __item },
+            quote::quote! { __item },
+            quote::quote! { __item },
+            quote::quote! { __entries.entry(#value); },
+        )
+    };
+
+    // Sorting collects the entries into a `Vec`, so it needs the `alloc`
+    // feature of derive_debug; without it the helper is reported missing at
+    // the attribute.
+    let entries = match sorted {
+        Some(sorted) => quote::quote_spanned! {sorted.span()=>
+            ::derive_debug::__private::sorted(#iterable, |#key_pattern| #key)
+        },
+        None => iterable,
+    };
+
+    quote::quote! {
+        &{
+            struct __Collection<__F: Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result>(__F);
+
+            impl<__F: Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result> ::core::fmt::Debug
+                for __Collection<__F>
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.0)(f)
+                }
+            }
+
+            __Collection(|f: &mut ::core::fmt::Formatter<'_>| {
+                let mut __entries = #builder;
+                for #pattern in #entries {
+                    #entry
+                }
+                __entries.finish()
+            })
+        }
+    }
+}
+
+/// Type arguments of a collection type, looking through a reference held by
+/// the field: the element of an array or slice, or the generic arguments of
+/// the last path segment, such as `K` and `V` in `HashMap<K, V>`.
+fn collection_arguments(ty: &syn::Type) -> Vec<&syn::Type> {
+    match ty {
+        syn::Type::Reference(reference) => collection_arguments(&reference.elem),
+        syn::Type::Array(array) => vec![&*array.elem],
+        syn::Type::Slice(slice) => vec![&*slice.elem],
+        syn::Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(syn::PathSegment {
+                arguments: syn::PathArguments::AngleBracketed(arguments),
+                ..
+            }) => arguments
+                .args
+                .iter()
+                .filter_map(|argument| match argument {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Adapter implementing `Debug` through a `fn(&T, &mut fmt::Formatter) ->
+/// fmt::Result` given by `#[debug(with = "...")]`, so that `T` itself does not
+/// need to implement `Debug`.
+fn debug_with(with: &syn::Path, access: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote::quote! {
+        &{
+            struct __DebugWith<'__a, __T: ?Sized>(
+                &'__a __T,
+                fn(&__T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            );
+
+            impl<__T: ?Sized> ::core::fmt::Debug for __DebugWith<'_, __T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
+            }
+
+            __DebugWith(#access, #with)
+        }
+    }
+}
+
+/// Adapter printing a field as one of the `#[debug(...)]` presets. Like the
+/// derived output, each one stays on a single line unless printed with
+/// `{:#?}`.
+fn preset_value(
+    preset: &Preset,
+    ty: &syn::Type,
+    access: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match preset {
+        Preset::Opaque => {
+            let name = type_name(ty);
+
+            quote::quote! { &::core::format_args!("<{}>", #name) }
+        }
+        Preset::Hex => radix(quote::quote! { ::core::fmt::LowerHex }, 2, 'x', access),
+        Preset::Binary => radix(quote::quote! { ::core::fmt::Binary }, 8, 'b', access),
+        Preset::Bytes => quote::quote! {
+            &{
+                struct __Bytes<'__a>(&'__a [u8]);
+
+                impl ::core::fmt::Debug for __Bytes<'_> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        if self.0.is_empty() {
+                            return f.write_str("[]");
+                        }
+
+                        if f.alternate() {
+                            for (line, bytes) in self.0.chunks(16).enumerate() {
+                                if line > 0 {
+                                    f.write_str("\n")?;
+                                }
+
+                                f.write_fmt(::core::format_args!("{:08x}:", line * 16))?;
+                                for byte in bytes {
+                                    f.write_fmt(::core::format_args!(" {:02x}", byte))?;
+                                }
+                            }
+
+                            ::core::result::Result::Ok(())
+                        } else {
+                            f.write_str("[")?;
+                            for (index, byte) in self.0.iter().enumerate() {
+                                if index > 0 {
+                                    f.write_str(" ")?;
+                                }
+
+                                f.write_fmt(::core::format_args!("{:02x}", byte))?;
+                            }
+                            f.write_str("]")
+                        }
+                    }
+                }
+
+                __Bytes(::core::convert::AsRef::<[u8]>::as_ref(#access))
+            }
+        },
+        Preset::Display => quote::quote! {
+            &{
+                struct __Display<'__a, __T: ?Sized>(&'__a __T);
+
+                impl<__T: ::core::fmt::Display + ?Sized> ::core::fmt::Debug for __Display<'_, __T> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::core::fmt::Display::fmt(self.0, f)
+                    }
+                }
+
+                __Display(#access)
+            }
+        },
+        Preset::Truncate(len) => quote::quote! {
+            &{
+                struct __Truncate<'__a, __T: ?Sized>(&'__a __T, usize);
+
+                impl<__T: ::core::fmt::Debug + ?Sized> ::core::fmt::Debug for __Truncate<'_, __T> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        struct __Limit<'__a, '__b> {
+                            f: &'__a mut ::core::fmt::Formatter<'__b>,
+                            remaining: usize,
+                            truncated: bool,
+                        }
+
+                        impl ::core::fmt::Write for __Limit<'_, '_> {
+                            fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+                                if self.truncated {
+                                    return ::core::result::Result::Ok(());
+                                }
+
+                                match s.char_indices().nth(self.remaining) {
+                                    ::core::option::Option::Some((index, _)) => {
+                                        self.truncated = true;
+                                        self.f.write_str(&s[..index])
+                                    }
+                                    ::core::option::Option::None => {
+                                        self.remaining -= s.chars().count();
+                                        self.f.write_str(s)
+                                    }
+                                }
+                            }
+                        }
+
+                        let alternate = f.alternate();
+                        let mut limit = __Limit {
+                            f,
+                            remaining: self.1,
+                            truncated: false,
+                        };
+
+                        if alternate {
+                            ::core::fmt::Write::write_fmt(&mut limit, ::core::format_args!("{:#?}", self.0))?;
+                        } else {
+                            ::core::fmt::Write::write_fmt(&mut limit, ::core::format_args!("{:?}", self.0))?;
+                        }
+
+                        if limit.truncated {
+                            limit.f.write_str("...")
+                        } else {
+                            ::core::result::Result::Ok(())
+                        }
+                    }
+                }
+
+                __Truncate(#access, #len)
+            }
+        },
+    }
+}
+
+/// Source text of a type as a user would write it, such as `Box<dyn Fn()>`,
+/// rather than with the spaces `TokenStream`'s `Display` puts between tokens.
+fn type_name(ty: &syn::Type) -> String {
+    let tokens = quote::quote!(#ty).to_string();
+    let mut name = String::with_capacity(tokens.len());
+    let mut chars = tokens.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == ' ' {
+            let joins_previous = name.ends_with(['<', '(', '[', '&', '*', ':']);
+            let joins_next = matches!(
+                chars.peek(),
+                Some('<' | '>' | '(' | ')' | '[' | ']' | ',' | ';' | ':')
+            );
+
+            if !name.ends_with(',') && (joins_previous || joins_next) {
+                continue;
+            }
+        }
+
+        name.push(ch);
+    }
+
+    name
+}
+
+/// Adapter for `#[debug(hex)]` and `#[debug(binary)]`, printing the value
+/// with its radix prefix, zero-padded to the full width of its type under
+/// `{:#?}`.
+fn radix(
+    format_trait: proc_macro2::TokenStream,
+    digits_per_byte: usize,
+    spec: char,
+    access: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let format = format!("{{:#{spec}}}");
+    let padded = format!("{{:#0width${spec}}}");
+
+    quote::quote! {
+        &{
+            struct __Radix<'__a, __T: ?Sized>(&'__a __T);
+
+            impl<__T: #format_trait + ?Sized> ::core::fmt::Debug for __Radix<'_, __T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    if f.alternate() {
+                        let width = 2 + #digits_per_byte * ::core::mem::size_of_val(self.0);
+                        f.write_fmt(::core::format_args!(#padded, self.0, width = width))
+                    } else {
+                        f.write_fmt(::core::format_args!(#format, self.0))
+                    }
+                }
+            }
+
+            __Radix(#access)
+        }
+    }
+}
+
+/// Placeholder printed instead of a secret value. `Len` requires the field to
+/// have a `len()` method and `Fingerprint` requires it to implement
+/// `AsRef<[u8]>`.
+fn redacted(redact: &Redact, access: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    match redact {
+        Redact::Full | Redact::Fingerprint(None) => {
+            quote::quote! { &::core::format_args!("<redacted>") }
+        }
+        Redact::Len => {
+            quote::quote! { &::core::format_args!("<redacted len={}>", (#access).len()) }
+        }
+        Redact::Fingerprint(Some(key)) => {
+            let key = quote::quote_spanned! {key.span()=> &#key };
+
+            quote::quote! {
+                &::core::format_args!(
+                    "<redacted #{:08x}>",
+                    ::derive_debug::__private::fingerprint(
+                        #key,
+                        ::core::convert::AsRef::<[u8]>::as_ref(#access),
+                    )
+                )
+            }
+        }
+    }
+}
+
+/// Bounds needed to format one field. A field-level `#[debug(bound = "...")]`
+/// replaces whatever would be inferred from the field's type.
+fn field_bounds(field: &DebugField, generic_types: &[&syn::Ident]) -> Vec<syn::WherePredicate> {
+    if let Some(ref predicates) = field.attrs.bounds {
+        return predicates.clone();
+    }
+
+    if field.attrs.skip || field.attrs.with.is_some() || field.attrs.redact.is_some() {
+        return Vec::new();
+    }
+
+    if let Some(ref template) = field.attrs.template {
+        return template_bounds(template, generic_types);
+    }
+
+    let format_trait = match field.attrs.preset {
+        Some((_, ref preset)) => match preset.format_trait() {
+            Some(format_trait) => format_trait,
+            None => return Vec::new(),
+        },
+        None if field.attrs.is_collection() => {
+            return collection_bounds(&field.attrs, &field.field.ty, generic_types)
+        }
+        None => syn::parse_quote!(::core::fmt::Debug),
+    };
+
+    // `AsRef<[u8]>` holds for the field as a whole, such as `Vec<u8>`, rather
+    // than for the type parameters within it.
+    if let Some((_, Preset::Bytes)) = field.attrs.preset {
+        let ty = &field.field.ty;
+        let visitor = BoundVisitor {
+            generic_types,
+            format_trait: &format_trait,
+            bounds: Vec::new(),
+        };
+
+        return if visitor.mentions_generic_types(ty) {
+            vec![syn::parse_quote!(#ty: #format_trait)]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let mut bounds = Vec::new();
+    type_bounds(&field.field.ty, &format_trait, generic_types, &mut bounds);
+
+    bounds
+}
+
+/// Bounds needed to print a collection field: `Debug` for the keys of a map,
+/// and for its items, or the values of a map, the traits used by
+/// `#[debug(each = "...")]`, `Debug` without it, or none with
+/// `#[debug(each_with = "...")]`. `#[debug(sorted)]` adds `Ord` for the keys
+/// of a map or the items of a set. Items are found among the type arguments,
+/// falling back to the whole type for collections that have none.
+fn collection_bounds(
+    attrs: &FieldAttrs,
+    ty: &syn::Type,
+    generic_types: &[&syn::Ident],
+) -> Vec<syn::WherePredicate> {
+    let debug: syn::Path = syn::parse_quote!(::core::fmt::Debug);
+    let item_traits = match attrs.each {
+        Some((_, Each::Format(ref lit_str))) => format_traits(lit_str),
+        Some((_, Each::With(_))) => Vec::new(),
+        None => vec![debug.clone()],
+    };
+
+    let arguments = collection_arguments(ty);
+    let (key, item) = match (attrs.collection(ty), arguments.as_slice()) {
+        (Collection::Map, [key, value, ..]) => (Some(*key), *value),
+        (Collection::Map, _) => (Some(ty), ty),
+        (_, [item, ..]) => (None, *item),
+        (_, []) => (None, ty),
+    };
+
+    let mut bounds = Vec::new();
+    if let Some(key) = key {
+        type_bounds(key, &debug, generic_types, &mut bounds);
+    }
+    for format_trait in &item_traits {
+        type_bounds(item, format_trait, generic_types, &mut bounds);
+    }
+    if attrs.sorted.is_some() {
+        let ord = syn::parse_quote!(::core::cmp::Ord);
+        type_bounds(key.unwrap_or(item), &ord, generic_types, &mut bounds);
+    }
+
+    bounds
+}
+
+/// Bounds needed to print a template: each field its placeholders refer to
+/// must implement the trait it is printed through. Other arguments, such as
+/// `self.0 * 2`, need a `#[debug(bound = "...")]`.
+fn template_bounds(template: &Template, generic_types: &[&syn::Ident]) -> Vec<syn::WherePredicate> {
+    let mut bounds = Vec::new();
+    for (ty, format_trait) in &template.formatted {
+        type_bounds(ty, format_trait, generic_types, &mut bounds);
+    }
+
+    bounds
+}
+
+/// Adds to `bounds` what must implement `format_trait` for `ty` to do so.
+fn type_bounds(
+    ty: &syn::Type,
+    format_trait: &syn::Path,
+    generic_types: &[&syn::Ident],
+    bounds: &mut Vec<syn::WherePredicate>,
+) {
+    let mut visitor = BoundVisitor {
+        generic_types,
+        format_trait,
+        bounds: Vec::new(),
+    };
+    visitor.visit_type(ty);
+
+    for bound in visitor.bounds {
+        if !bounds.contains(&bound) {
+            bounds.push(bound);
+        }
+    }
+}
+
+/// Walks a field type collecting what must implement `format_trait`, usually
+/// `Debug`, for the field to do so: type parameters used directly and
+/// associated types of them, such as `T`, `T::Value` or `<T as Trait>::Value`.
+/// Types that are `Debug` whatever their parameters, like `PhantomData<T>` or
+/// `fn() -> T`, are not descended into.
+struct BoundVisitor<'a> {
+    generic_types: &'a [&'a syn::Ident],
+    format_trait: &'a syn::Path,
+    bounds: Vec<syn::WherePredicate>,
+}
+
+impl BoundVisitor<'_> {
+    fn push(&mut self, type_path: &syn::TypePath) {
+        let format_trait = self.format_trait;
+        let bound: syn::WherePredicate = syn::parse_quote!(#type_path: #format_trait);
+
+        if !self.bounds.contains(&bound) {
+            self.bounds.push(bound);
+        }
+    }
+
+    fn mentions_generic_types(&self, ty: &syn::Type) -> bool {
+        let mut visitor = BoundVisitor {
+            generic_types: self.generic_types,
+            format_trait: self.format_trait,
+            bounds: Vec::new(),
+        };
+        visitor.visit_type(ty);
+
+        !visitor.bounds.is_empty()
+    }
+}
+
+impl<'ast> syn::visit::Visit<'ast> for BoundVisitor<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast syn::TypePath) {
+        if let Some(ref qself) = type_path.qself {
+            if self.mentions_generic_types(&qself.ty) {
+                self.push(type_path);
+            }
+
+            return;
+        }
+
+        let path = &type_path.path;
+        if path.leading_colon.is_none() && self.generic_types.contains(&&path.segments[0].ident) {
+            self.push(type_path);
+
+            return;
+        }
+
+        if let Some(last) = path.segments.last() {
+            if last.ident == "PhantomData" {
+                return;
+            }
+        }
+
+        syn::visit::visit_type_path(self, type_path)
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+
+    fn visit_type_ptr(&mut self, _: &'ast syn::TypePtr) {}
+
+    fn visit_type_trait_object(&mut self, _: &'ast syn::TypeTraitObject) {}
+
+    fn visit_type_macro(&mut self, _: &'ast syn::TypeMacro) {}
+}
+
+fn add_impl_generics_bounds(
+    mut generics: syn::Generics,
+    inferred: &[syn::WherePredicate],
+    predicates: Option<&[syn::WherePredicate]>,
+) -> syn::Generics {
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .extend(predicates.unwrap_or(inferred).iter().cloned());
+
+    generics
+}
+
+/// A field of the input together with its parsed `#[debug]` attributes.
+struct DebugField<'a> {
+    field: &'a syn::Field,
+    attrs: FieldAttrs,
+}
+
+fn debug_fields<'a>(fields: &'a syn::Fields, container: &ContainerAttrs) -> Vec<DebugField<'a>> {
+    fields
+        .iter()
+        .map(|field| {
+            let mut attrs = field_attrs(&field.attrs);
+
+            if let (None, Some(ref flatten)) = (&field.ident, &attrs.flatten) {
+                proc_macro_error::emit_error!(
+                    flatten.span(),
+                    "`debug(flatten)` expected named field"
+                );
+            }
+
+            if let Some(ref sorted) = attrs.sorted {
+                if attrs.collection(&field.ty) == Collection::List {
+                    proc_macro_error::emit_error!(
+                        sorted.span(),
+                        "`debug(sorted)` expected a map or a set, mark types not named like one with `debug(map)` or `debug(set)`"
+                    );
+                }
+            }
+
+            if let (None, Some(ref rename)) = (&field.ident, &attrs.rename) {
+                proc_macro_error::emit_error!(
+                    rename.span(),
+                    "positional fields are printed without a name"
+                );
+            }
+
+            if let Some(template) = attrs.template.take() {
+                match expand_template(&template, fields) {
+                    Ok(template) => attrs.template = Some(template),
+                    Err(error) => {
+                        emit_errors(error);
+                    }
+                }
+            }
+
+            if let Some(ref except) = container.redact_all_except {
+                let excepted = field
+                    .ident
+                    .as_ref()
+                    .is_some_and(|ident| except.contains(ident));
+
+                if !excepted && attrs.redact.is_none() {
+                    attrs.redact = Some(Redact::Full);
+                }
+            }
+
+            DebugField { field, attrs }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    bounds: Option<Vec<syn::WherePredicate>>,
+    redact_all_except: Option<Vec<syn::Ident>>,
+    rename: Option<syn::LitStr>,
+    transparent: Option<syn::Path>,
+    template: Option<Template>,
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    rename: Option<syn::LitStr>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    format: Option<syn::LitStr>,
+    bounds: Option<Vec<syn::WherePredicate>>,
+    skip: bool,
+    skip_if: Option<syn::Path>,
+    with: Option<syn::Path>,
+    redact: Option<Redact>,
+    rename: Option<syn::LitStr>,
+    flatten: Option<syn::Path>,
+    template: Option<Template>,
+    preset: Option<(syn::Path, Preset)>,
+    each: Option<(syn::Path, Each)>,
+    sorted: Option<syn::Path>,
+    collection: Option<(syn::Path, Collection)>,
+}
+
+impl FieldAttrs {
+    /// Whether the field is printed item by item.
+    fn is_collection(&self) -> bool {
+        self.each.is_some() || self.sorted.is_some() || self.collection.is_some()
+    }
+
+    /// How a collection field of type `ty` is printed: as given by
+    /// `#[debug(map)]` or `#[debug(set)]`, or else judging by the name of its
+    /// type, such as `HashMap` or `BTreeSet`.
+    fn collection(&self, ty: &syn::Type) -> Collection {
+        if let Some((_, collection)) = self.collection {
+            return collection;
+        }
+
+        let name = match ty {
+            syn::Type::Reference(reference) => return self.collection(&reference.elem),
+            syn::Type::Path(type_path) => match type_path.path.segments.last() {
+                Some(segment) => segment.ident.to_string(),
+                None => return Collection::List,
+            },
+            _ => return Collection::List,
+        };
+
+        if name.ends_with("Map") {
+            Collection::Map
+        } else if name.ends_with("Set") {
+            Collection::Set
+        } else {
+            Collection::List
+        }
+    }
+}
+
+/// Debug builder a collection field is printed with. Maps iterate key/value
+/// pairs.
+#[derive(Clone, Copy, PartialEq)]
+enum Collection {
+    List,
+    Set,
+    Map,
+}
+
+/// Format applied to every item of a collection.
+enum Each {
+    Format(syn::LitStr),
+    With(syn::Path),
+}
+
+/// Format string and arguments of `#[debug(fmt = "...", args(...))]`, where
+/// the arguments are expressions that may refer to the struct's fields
+/// through `self`.
+struct Template {
+    format: syn::LitStr,
+    args: Vec<syn::Expr>,
+    /// Types of the fields printed by the placeholders, with the trait each
+    /// is printed through, filled in by `expand_template`.
+    formatted: Vec<(syn::Type, syn::Path)>,
+}
+
+/// Common formats selected by a bare key, such as `#[debug(hex)]`.
+enum Preset {
+    /// The field's type name, for types that do not implement `Debug`.
+    Opaque,
+    Hex,
+    Binary,
+    /// Hex dump of an `AsRef<[u8]>` value such as `Vec<u8>` or `[u8; N]`.
+    Bytes,
+    /// The field's `Display` output.
+    Display,
+    /// The field's `Debug` output, cut after this many characters.
+    Truncate(usize),
+}
+
+impl Preset {
+    /// Trait the field is printed through, if any.
+    fn format_trait(&self) -> Option<syn::Path> {
+        match self {
+            Preset::Opaque => None,
+            Preset::Hex => Some(syn::parse_quote!(::core::fmt::LowerHex)),
+            Preset::Binary => Some(syn::parse_quote!(::core::fmt::Binary)),
+            Preset::Bytes => Some(syn::parse_quote!(::core::convert::AsRef<[u8]>)),
+            Preset::Display => Some(syn::parse_quote!(::core::fmt::Display)),
+            Preset::Truncate(_) => Some(syn::parse_quote!(::core::fmt::Debug)),
+        }
+    }
+}
+
+/// How much of a secret field `#[debug(redact)]` lets through.
+enum Redact {
+    Full,
+    Len,
+    /// Hash of the field's bytes keyed by the static given with `key = "..."`,
+    /// filled in by `field_attrs` once every option is parsed.
+    Fingerprint(Option<syn::Path>),
+}
+
+fn container_attrs(attrs: &[syn::Attribute]) -> ContainerAttrs {
+    let mut container = ContainerAttrs::default();
+    let mut format = None;
+    let mut args = None;
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+
+        parse_debug_list(attr, |meta| {
+            if meta.path.is_ident("bound") {
+                extend_bounds(&mut container.bounds, meta)
+            } else if meta.path.is_ident("rename") {
+                container.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                container.transparent = Some(meta.path);
+                Ok(())
+            } else if meta.path.is_ident("fmt") {
+                format = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("args") {
+                args = Some(template_args(meta)?);
+                Ok(())
+            } else if meta.path.is_ident("redact_all_except") {
+                let except = container.redact_all_except.get_or_insert_with(Vec::new);
+
+                meta.parse_nested_meta(|nested| match nested.path.get_ident() {
+                    Some(ident) => {
+                        except.push(ident.clone());
+                        Ok(())
+                    }
+                    None => Err(nested.error("expected field name")),
+                })
+            } else {
+                Err(meta.error("unrecognized debug attribute"))
+            }
+        });
+    }
+
+    container.template = template(format, args);
+
+    container
+}
+
+fn variant_attrs(attrs: &[syn::Attribute]) -> VariantAttrs {
+    let mut variant = VariantAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+
+        parse_debug_list(attr, |meta| {
+            if meta.path.is_ident("rename") {
+                variant.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized debug attribute"))
+            }
+        });
+    }
+
+    variant
+}
+
+fn field_attrs(attrs: &[syn::Attribute]) -> FieldAttrs {
+    let mut field = FieldAttrs::default();
+    let mut format = None;
+    let mut args = None;
+    let mut redact_path = None;
+    let mut key = None;
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+
+        match attr.meta {
+            syn::Meta::NameValue(ref meta) => match meta.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(ref lit_str),
+                    ..
+                }) => match check_format(lit_str) {
+                    Ok(()) => field.format = Some(lit_str.clone()),
+                    Err(error) => proc_macro_error::emit_error!(error.span(), "{}", error),
+                },
+                _ => {
+                    proc_macro_error::emit_error!(meta.value.span(), "value of debug is not string")
+                }
+            },
+            syn::Meta::List(_) => parse_debug_list(attr, |meta| {
+                if meta.path.is_ident("bound") {
+                    extend_bounds(&mut field.bounds, meta)
+                } else if meta.path.is_ident("skip") {
+                    field.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("skip_if") {
+                    let lit_str: syn::LitStr = meta.value()?.parse()?;
+                    field.skip_if = Some(lit_str.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let lit_str: syn::LitStr = meta.value()?.parse()?;
+                    field.with = Some(lit_str.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("redact") {
+                    redact_path = Some(meta.path.clone());
+                    field.redact = Some(redact(meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("key") {
+                    let lit_str: syn::LitStr = meta.value()?.parse()?;
+                    key = Some((meta.path, lit_str.parse::<syn::Path>()?));
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    field.rename = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    field.flatten = Some(meta.path);
+                    Ok(())
+                } else if meta.path.is_ident("fmt") {
+                    format = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("args") {
+                    args = Some(template_args(meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("each") {
+                    let lit_str: syn::LitStr = meta.value()?.parse()?;
+                    check_format(&lit_str)?;
+                    field.each = Some((meta.path, Each::Format(lit_str)));
+                    Ok(())
+                } else if meta.path.is_ident("sorted") {
+                    field.sorted = Some(meta.path);
+                    Ok(())
+                } else if meta.path.is_ident("each_with") {
+                    let lit_str: syn::LitStr = meta.value()?.parse()?;
+                    field.each = Some((meta.path, Each::With(lit_str.parse()?)));
+                    Ok(())
+                } else if meta.path.is_ident("map") || meta.path.is_ident("set") {
+                    if field.collection.is_some() {
+                        return Err(
+                            meta.error("a field is printed either as a `map` or as a `set`")
+                        );
+                    }
+
+                    let collection = if meta.path.is_ident("map") {
+                        Collection::Map
+                    } else {
+                        Collection::Set
+                    };
+                    field.collection = Some((meta.path, collection));
+                    Ok(())
+                } else if let Some(preset) = preset(&meta)? {
+                    if field.preset.is_some() {
+                        return Err(meta.error("a field accepts a single preset"));
+                    }
+
+                    field.preset = Some((meta.path, preset));
+                    Ok(())
+                } else {
+                    Err(meta.error("unrecognized debug attribute"))
+                }
+            }),
+            syn::Meta::Path(_) => proc_macro_error::emit_error!(
+                attr.meta.span(),
+                "attribute macro expected format `#[debug = \"formatter\"]`"
+            ),
+        }
+    }
+
+    field.template = template(format, args);
+
+    match (&mut field.redact, key) {
+        (Some(Redact::Fingerprint(slot)), Some((_, key))) => *slot = Some(key),
+        (Some(Redact::Fingerprint(_)), None) => proc_macro_error::emit_error!(
+            redact_path.span(),
+            "`debug(redact = \"fingerprint\")` expected `key = \"...\"`, naming a secret `[u8; 16]`"
+        ),
+        (_, Some((path, _))) => proc_macro_error::emit_error!(
+            path.span(),
+            "`debug(key = \"...\")` expected `redact = \"fingerprint\"`"
+        ),
+        (_, None) => {}
+    }
+
+    if let (Some(ref with), true) = (
+        &field.with,
+        field.format.is_some() || field.template.is_some(),
+    ) {
+        proc_macro_error::emit_error!(
+            with.span(),
+            "`debug(with = \"...\")` cannot be combined with a format string"
+        );
+    }
+
+    if let (Some((ref path, _)), true) = (
+        &field.preset,
+        field.format.is_some() || field.with.is_some() || field.template.is_some(),
+    ) {
+        proc_macro_error::emit_error!(
+            path.span(),
+            "`debug({})` cannot be combined with a format string",
+            path.get_ident().unwrap()
+        );
+    }
+
+    let collection = field.each.as_ref().map(|(path, _)| path);
+    if let (Some(path), true) = (
+        collection
+            .or(field.sorted.as_ref())
+            .or(field.collection.as_ref().map(|(path, _)| path)),
+        field.format.is_some()
+            || field.with.is_some()
+            || field.template.is_some()
+            || field.preset.is_some(),
+    ) {
+        proc_macro_error::emit_error!(
+            path.span(),
+            "`debug({})` cannot be combined with another format",
+            path.get_ident().unwrap()
+        );
+    }
+
+    if let (Some(_), Some(ref template)) = (&field.format, &field.template) {
+        proc_macro_error::emit_error!(
+            template.format.span(),
+            "`debug(fmt = \"...\")` cannot be combined with `#[debug = \"...\"]`"
+        );
+    }
+
+    field
+}
+
+fn preset(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<Preset>> {
+    let preset = if meta.path.is_ident("opaque") {
+        Preset::Opaque
+    } else if meta.path.is_ident("hex") {
+        Preset::Hex
+    } else if meta.path.is_ident("binary") {
+        Preset::Binary
+    } else if meta.path.is_ident("bytes") {
+        Preset::Bytes
+    } else if meta.path.is_ident("display") {
+        Preset::Display
+    } else if meta.path.is_ident("truncate") {
+        let lit_int: syn::LitInt = meta.value()?.parse()?;
+        Preset::Truncate(lit_int.base10_parse()?)
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(preset))
+}
+
+fn redact(meta: syn::meta::ParseNestedMeta) -> syn::Result<Redact> {
+    if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+        return Ok(Redact::Full);
+    }
+
+    let lit_str: syn::LitStr = meta.value()?.parse()?;
+    match lit_str.value().as_str() {
+        "len" => Ok(Redact::Len),
+        "fingerprint" => Ok(Redact::Fingerprint(None)),
+        _ => Err(syn::Error::new(
+            lit_str.span(),
+            "expected `redact`, `redact = \"len\"` or `redact = \"fingerprint\"`",
+        )),
+    }
+}
+
+/// Parses the expressions of `args(...)`, keeping the `args` path to report
+/// arguments given without a format string.
+fn template_args(meta: syn::meta::ParseNestedMeta) -> syn::Result<(syn::Path, Vec<syn::Expr>)> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    let args =
+        syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated(&content)?;
+
+    Ok((meta.path, args.into_iter().collect()))
+}
+
+fn template(
+    format: Option<syn::LitStr>,
+    args: Option<(syn::Path, Vec<syn::Expr>)>,
+) -> Option<Template> {
+    match (format, args) {
+        (Some(format), args) => Some(Template {
+            format,
+            args: args.map(|(_, args)| args).unwrap_or_default(),
+            formatted: Vec::new(),
+        }),
+        (None, Some((path, _))) => {
+            proc_macro_error::emit_error!(
+                path.span(),
+                "`debug(args(...))` expected `fmt = \"...\"`"
+            );
+            None
+        }
+        (None, None) => None,
+    }
+}
+
+/// Parses the nested options of `#[debug(...)]`, reporting any error at its
+/// own span instead of discarding the attribute.
+fn parse_debug_list(
+    attr: &syn::Attribute,
+    logic: impl FnMut(syn::meta::ParseNestedMeta) -> syn::Result<()>,
+) {
+    if let Err(error) = attr.parse_nested_meta(logic) {
+        proc_macro_error::emit_error!(error.span(), "{}", error);
+    }
+}
+
+/// Adds the comma-separated predicates of `bound = "..."` to `bounds`. Every
+/// bound attribute contributes, so they may be split across attributes.
+fn extend_bounds(
+    bounds: &mut Option<Vec<syn::WherePredicate>>,
+    meta: syn::meta::ParseNestedMeta,
+) -> syn::Result<()> {
+    let lit_str: syn::LitStr = meta.value()?.parse()?;
+    let predicates = lit_str
+        .parse_with(
+            syn::punctuated::Punctuated::<syn::WherePredicate, syn::Token![,]>::parse_terminated,
+        )
+        .map_err(|error| syn::Error::new(lit_str.span(), format!("invalid bound: {error}")))?;
+
+    bounds.get_or_insert_with(Vec::new).extend(predicates);
+
+    Ok(())
+}
+
+/// Checks a template against the struct's `fields` and rewrites its inline
+/// `{self.field}` placeholders into positional ones, appending the fields to
+/// the arguments, since `format_args!` only captures plain identifiers.
+fn expand_template(template: &Template, fields: &syn::Fields) -> syn::Result<Template> {
+    let lit_str = &template.format;
+    let error = |message: String| syn::Error::new(lit_str.span(), message);
+
+    let mut visitor = SelfFieldVisitor {
+        fields,
+        errors: Vec::new(),
+    };
+    for arg in &template.args {
+        visitor.visit_expr(arg);
+    }
+    let mut errors = visitor.errors;
+
+    let mut args = template.args.clone();
+    let mut used = vec![false; args.len()];
+    let mut implicit = 0;
+    let mut format = String::new();
+    let mut formatted = Vec::new();
+
+    for piece in parse_format(&lit_str.value()).map_err(error)? {
+        let (argument, spec) = match piece {
+            FormatPiece::Text(text) => {
+                format.push_str(&text.replace('{', "{{").replace('}', "}}"));
+                continue;
+            }
+            FormatPiece::Placeholder { argument, spec } => (argument, spec),
+        };
+
+        let format_trait = spec_trait(check_format_spec(&spec).map_err(error)?);
+
+        let index = if let Some(member) = argument.strip_prefix("self.") {
+            let member: syn::Member = syn::parse_str(member)
+                .map_err(|_| error(format!("expected `self.field`, found `{argument}`")))?;
+
+            if !fields.members().any(|field| field == member) {
+                errors.push(error(format!("no field `{}`", member_name(&member))));
+            }
+
+            args.push(syn::parse_quote_spanned!(lit_str.span()=> self.#member));
+            args.len() - 1
+        } else {
+            let index = if argument.is_empty() {
+                implicit += 1;
+                implicit - 1
+            } else {
+                argument.parse::<usize>().map_err(|_| {
+                    error(format!(
+                        "named argument `{argument}` is not supported, use `{{self.{argument}}}` or `args(...)`"
+                    ))
+                })?
+            };
+
+            match used.get_mut(index) {
+                Some(used) => *used = true,
+                None => {
+                    return Err(error(format!(
+                        "format string refers to argument {index}, but {}",
+                        match used.len() {
+                            0 => String::from("no arguments are given"),
+                            1 => String::from("only 1 argument is given"),
+                            len => format!("only {len} arguments are given"),
+                        }
+                    )))
+                }
+            }
+
+            index
+        };
+
+        if let Some(ty) = self_field_type(&args[index], fields) {
+            formatted.push((ty.clone(), format_trait));
+        }
+
+        if spec.is_empty() {
+            format.push_str(&format!("{{{index}}}"));
+        } else {
+            format.push_str(&format!("{{{index}:{spec}}}"));
+        }
+    }
+
+    for (arg, used) in template.args.iter().zip(used) {
+        if !used {
+            errors.push(syn::Error::new(
+                arg.span(),
+                "argument never used in format string",
+            ));
+        }
+    }
+
+    let mut errors = errors.into_iter();
+    if let Some(mut combined) = errors.next() {
+        combined.extend(errors);
+        return Err(combined);
+    }
+
+    Ok(Template {
+        format: syn::LitStr::new(&format, lit_str.span()),
+        args,
+        formatted,
+    })
+}
+
+/// Type of the field read by a template argument that is only `self.field`.
+fn self_field_type<'a>(arg: &syn::Expr, fields: &'a syn::Fields) -> Option<&'a syn::Type> {
+    let syn::Expr::Field(expr_field) = arg else {
+        return None;
+    };
+
+    match *expr_field.base {
+        syn::Expr::Path(ref base) if base.path.is_ident("self") => fields
+            .iter()
+            .zip(fields.members())
+            .find(|(_, member)| *member == expr_field.member)
+            .map(|(field, _)| &field.ty),
+        _ => None,
+    }
+}
+
+fn template_value(template: &Template) -> proc_macro2::TokenStream {
+    let Template { format, args, .. } = template;
+
+    quote::quote! { &::core::format_args!(#format, #(#args),*) }
+}
+
+/// Reports every error combined in `error`, returning a placeholder value for
+/// the expansion, which is discarded along with the errors.
+fn emit_errors(error: syn::Error) -> proc_macro2::TokenStream {
+    for error in error {
+        proc_macro_error::emit_error!(error.span(), "{}", error);
+    }
+
+    quote::quote! { &"" }
+}
+
+fn member_name(member: &syn::Member) -> String {
+    match member {
+        syn::Member::Named(ident) => ident.unraw().to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+/// Checks that every `self.field` in template arguments names a field of the
+/// struct.
+struct SelfFieldVisitor<'a> {
+    fields: &'a syn::Fields,
+    errors: Vec<syn::Error>,
+}
+
+impl<'ast> syn::visit::Visit<'ast> for SelfFieldVisitor<'_> {
+    fn visit_expr_field(&mut self, expr_field: &'ast syn::ExprField) {
+        if let syn::Expr::Path(ref base) = *expr_field.base {
+            if base.path.is_ident("self")
+                && !self
+                    .fields
+                    .members()
+                    .any(|field| field == expr_field.member)
+            {
+                self.errors.push(syn::Error::new(
+                    expr_field.member.span(),
+                    format!("no field `{}`", member_name(&expr_field.member)),
+                ));
+            }
+        }
+
+        syn::visit::visit_expr_field(self, expr_field)
+    }
+}
+
+/// Template of `#[display("...")]` on a struct or variant, which is required.
+fn display_attr(attrs: &[syn::Attribute], ident: &syn::Ident) -> Option<syn::LitStr> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("display")) else {
+        proc_macro_error::emit_error!(
+            ident.span(),
+            "derive(CustomDisplay) expected `#[display(\"...\")]` on `{}`",
+            ident
+        );
+        return None;
+    };
+
+    match attr.parse_args() {
+        Ok(lit_str) => Some(lit_str),
+        Err(error) => {
+            proc_macro_error::emit_error!(error.span(), "{}", error);
+            None
+        }
+    }
+}
+
+/// Writes a `#[display("...")]` template, whose placeholders name `fields`
+/// as `{field}` or `{0}`, where `accesses` holds a reference expression to
+/// each field in order. Each field used adds the bounds its formatting trait
+/// needs to `inferred`.
+fn display_template(
+    lit_str: &syn::LitStr,
+    fields: &syn::Fields,
+    accesses: &[proc_macro2::TokenStream],
+    generic_types: &[&syn::Ident],
+    inferred: &mut Vec<syn::WherePredicate>,
+) -> proc_macro2::TokenStream {
+    let error = |message: String| {
+        proc_macro_error::emit_error!(lit_str.span(), "{}", message);
+        quote::quote! { ::core::result::Result::Ok(()) }
+    };
+
+    let pieces = match parse_format(&lit_str.value()) {
+        Ok(pieces) => pieces,
+        Err(message) => return error(message),
+    };
+
+    let mut used = Vec::new();
+    let mut format = String::new();
+
+    for piece in pieces {
+        let (argument, spec) = match piece {
+            FormatPiece::Text(text) => {
+                format.push_str(&text.replace('{', "{{").replace('}', "}}"));
+                continue;
+            }
+            FormatPiece::Placeholder { argument, spec } => (argument, spec),
+        };
+
+        if argument.is_empty() {
+            return error(String::from(
+                "placeholder expected field name, such as `{field}` or `{0}`",
+            ));
+        }
+
+        let Some(position) = fields
+            .members()
+            .position(|member| member_name(&member) == argument)
+        else {
+            return error(format!("no field `{argument}`"));
+        };
+
+        let format_trait = match check_format_spec(&spec) {
+            Ok(ty) => spec_trait(ty),
+            Err(message) => return error(message),
+        };
+
+        let ty = &fields.iter().nth(position).unwrap().ty;
+        type_bounds(ty, &format_trait, generic_types, inferred);
+
+        let index = match used.iter().position(|&used| used == position) {
+            Some(index) => index,
+            None => {
+                used.push(position);
+                used.len() - 1
+            }
+        };
+
+        if spec.is_empty() {
+            format.push_str(&format!("{{{index}}}"));
+        } else {
+            format.push_str(&format!("{{{index}:{spec}}}"));
+        }
+    }
+
+    let format = syn::LitStr::new(&format, lit_str.span());
+    let args = used.iter().map(|&position| &accesses[position]);
+
+    quote::quote! {
+        f.write_fmt(::core::format_args!(#format, #(#args),*))
+    }
+}
+
+/// Checks a `#[debug = "..."]` format string before it reaches `format_args!`,
+/// whose errors would point at the derive rather than at the attribute. The
+/// string must refer to the field, its only argument, through `{}` or `{0}`
+/// placeholders with a valid format spec.
+fn check_format(lit_str: &syn::LitStr) -> syn::Result<()> {
+    let error = |message: String| syn::Error::new(lit_str.span(), message);
+
+    let pieces = parse_format(&lit_str.value()).map_err(error)?;
+    let mut implicit = 0;
+    let mut placeholders = 0;
+
+    for piece in pieces {
+        let FormatPiece::Placeholder { argument, spec } = piece else {
+            continue;
+        };
+
+        let index = if argument.is_empty() {
+            implicit += 1;
+            implicit - 1
+        } else if let Ok(index) = argument.parse::<usize>() {
+            index
+        } else {
+            return Err(error(format!(
+                "named argument `{argument}` is not supported, refer to the field with `{{}}`"
+            )));
+        };
+
+        if index != 0 {
+            return Err(error(format!(
+                "format string must use exactly one argument, the field, but refers to argument {index}"
+            )));
+        }
+
+        check_format_spec(&spec).map_err(error)?;
+        placeholders += 1;
+    }
+
+    if placeholders == 0 {
+        return Err(error(String::from(
+            "format string must use exactly one argument, the field, but has no `{}` placeholder",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Formatting traits used by the placeholders of a format string already
+/// accepted by `check_format`.
+fn format_traits(lit_str: &syn::LitStr) -> Vec<syn::Path> {
+    let mut format_traits = Vec::new();
+
+    for piece in parse_format(&lit_str.value()).unwrap_or_default() {
+        let FormatPiece::Placeholder { spec, .. } = piece else {
+            continue;
+        };
+
+        if let Ok(ty) = check_format_spec(&spec) {
+            let format_trait = spec_trait(ty);
+            if !format_traits.contains(&format_trait) {
+                format_traits.push(format_trait);
+            }
+        }
+    }
+
+    format_traits
+}
+
+/// Formatting trait selected by the type of a format spec, such as `x` in
+/// `{:x}`.
+fn spec_trait(ty: &str) -> syn::Path {
+    match ty {
+        "" => syn::parse_quote!(::core::fmt::Display),
+        "?" | "x?" | "X?" => syn::parse_quote!(::core::fmt::Debug),
+        "x" => syn::parse_quote!(::core::fmt::LowerHex),
+        "X" => syn::parse_quote!(::core::fmt::UpperHex),
+        "o" => syn::parse_quote!(::core::fmt::Octal),
+        "b" => syn::parse_quote!(::core::fmt::Binary),
+        "e" => syn::parse_quote!(::core::fmt::LowerExp),
+        "E" => syn::parse_quote!(::core::fmt::UpperExp),
+        _ => syn::parse_quote!(::core::fmt::Pointer),
+    }
+}
+
+/// A piece of a format string: literal text with its `{{` and `}}` escapes
+/// resolved, or a `{argument:spec}` placeholder.
+enum FormatPiece {
+    Text(String),
+    Placeholder { argument: String, spec: String },
+}
+
+fn parse_format(format: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' => {
+                return Err(String::from(
+                    "unmatched `}` in format string, use `}}` to escape it",
+                ))
+            }
+            '{' => {
+                let mut placeholder = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => placeholder.push(ch),
+                        None => {
+                            return Err(String::from(
+                                "unterminated `{` in format string, use `{{` to escape it",
+                            ))
+                        }
+                    }
+                }
+
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                }
+
+                let (argument, spec) = match placeholder.split_once(':') {
+                    Some((argument, spec)) => (argument, spec),
+                    None => (placeholder.as_str(), ""),
+                };
+
+                pieces.push(FormatPiece::Placeholder {
+                    argument: argument.trim().to_owned(),
+                    spec: spec.to_owned(),
+                });
+            }
+            _ => text.push(ch),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+
+    Ok(pieces)
+}
+
+/// Checks the part of a placeholder after `:`, following the grammar
+/// `[[fill]align][sign]['#']['0'][width]['.' precision][type]`. Widths and
+/// precisions taken from other arguments are rejected since the field is the
+/// only one. Returns the type, which selects the formatting trait.
+fn check_format_spec(spec: &str) -> Result<&str, String> {
+    let mut rest = spec;
+
+    let mut chars = rest.chars();
+    let first = chars.next();
+    let second = chars.next();
+
+    if matches!(second, Some('<' | '^' | '>')) {
+        rest = &rest[first.unwrap().len_utf8() + 1..];
+    } else if matches!(first, Some('<' | '^' | '>')) {
+        rest = &rest[1..];
+    }
+
+    rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+    rest = rest.strip_prefix('#').unwrap_or(rest);
+    rest = rest.strip_prefix('0').unwrap_or(rest);
+    rest = rest.trim_start_matches(|ch: char| ch.is_ascii_digit());
+
+    if let Some(precision) = rest.strip_prefix('.') {
+        if precision.starts_with('*') {
+            return Err(String::from(
+                "precision `.*` takes another argument, but the field is the only one",
+            ));
+        }
+
+        rest = precision.trim_start_matches(|ch: char| ch.is_ascii_digit());
+    }
+
+    if rest.contains('$') {
+        return Err(String::from(
+            "width and precision taken from arguments are not supported, the field is the only one",
+        ));
+    }
+
+    match rest {
+        "" | "?" | "x?" | "X?" | "x" | "X" | "o" | "b" | "e" | "E" | "p" => Ok(rest),
+        _ => Err(format!("unknown format trait `{rest}`")),
+    }
+}
@@ -1,229 +1,126 @@
-use syn::spanned::Spanned;
-
-#[proc_macro_error::proc_macro_error]
-#[proc_macro_derive(CustomDebug, attributes(debug))]
-pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let ast: syn::DeriveInput = syn::parse_macro_input!(input);
-
-    // dbg!(&ast);
-
-    let struct_ident = &ast.ident;
-    let data_struct = match &ast.data {
-        syn::Data::Struct(data_struct) => data_struct,
-        _ => proc_macro_error::abort_call_site!("derive(CustomDebug) expected struct"),
-    };
-
-    let named = match &data_struct.fields {
-        syn::Fields::Named(fields) => fields.named.clone(),
-        _ => proc_macro_error::abort_call_site!(
-            "derive(CustomDebug) expected struct with named fields"
-        ),
-    };
-
-    let struct_fields_names = named.iter().map(|field: &syn::Field| &field.ident);
-
-    let formatter_fields_names = named.iter().map(|field: &syn::Field| {
-        for attr in &field.attrs {
-            if attr.path().is_ident("debug") {
-                if let syn::Meta::NameValue(ref meta, ..) = attr.meta {
-                    if let syn::Expr::Lit(ref expr) = meta.value {
-                        if let syn::Lit::Str(ref lit_str) = expr.lit {
-                            return lit_str.value();
-                        }
-                    }
-                } else {
-                    proc_macro_error::abort_call_site!(
-                        "attribute macro expected format `#[debug = \"formatter\"]`"
-                    )
-                }
-            }
-        }
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros, so the Inspect and FieldVisitor traits implemented by
+// derive(CustomDebug) are defined in this crate, which re-exports the derives
+// from derive_debug-impl. Users only need to depend on this one crate.
 
-        String::from("{:?}")
-    });
-
-    fn inner_ty(ty: &syn::Type, outer: Option<&str>) -> std::option::Option<syn::Type> {
-        if let syn::Type::Path(ref ty_path) = ty {
-            if ty_path.path.segments.len() != 1 {
-                return std::option::Option::None;
-            }
-
-            if let Some(outer_ty) = outer {
-                if ty_path.path.segments[0].ident != outer_ty {
-                    return std::option::Option::None;
-                }
-            }
-
-            if let syn::PathArguments::AngleBracketed(ref angle_bracketed) =
-                ty_path.path.segments[0].arguments
-            {
-                if let syn::GenericArgument::Type(ref unwrapped_inner_ty) = angle_bracketed.args[0]
-                {
-                    return std::option::Option::Some(unwrapped_inner_ty.clone());
-                }
-            }
-        }
+#![no_std]
 
-        std::option::Option::None
-    }
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-    fn associated_ty(ty: &syn::Type, generic_types: &[&syn::Ident]) -> Option<syn::TypePath> {
-        if let Some(inner_ty) = inner_ty(ty, None) {
-            return associated_ty(&inner_ty, &generic_types).clone();
-        }
+pub use derive_debug_impl::{CustomDebug, CustomDisplay};
 
-        if let syn::Type::Path(type_path) = ty {
-            if type_path.path.segments.len() < 2 {
-                return None;
-            }
+use core::fmt::Debug;
 
-            let type_ident = &type_path.path.segments[0].ident;
-            if generic_types.contains(&type_ident) {
-                return Some(type_path.clone());
-            }
-        }
+/// Structured access to the fields `derive(CustomDebug)` prints, for example
+/// to emit them as key/value records without going through a string.
+pub trait Inspect {
+    /// Passes each printed field to `v`, in declaration order, under its
+    /// printed name and formatted as in the `Debug` output. Skipped fields are
+    /// left out, renamed fields use their new name, redacted fields pass their
+    /// placeholder, and flattened fields pass their own fields.
+    fn visit(&self, v: &mut dyn FieldVisitor);
+}
+
+/// Receives the fields of an [`Inspect`] value.
+pub trait FieldVisitor {
+    /// Called once per field. Positional fields are named by their index,
+    /// such as `"0"`.
+    fn visit_field(&mut self, name: &str, value: &dyn Debug);
+}
 
-        None
+#[doc(hidden)]
+pub mod __private {
+    use core::fmt::DebugStruct;
+
+    /// Adds the fields of a struct deriving `CustomDebug` to the output of
+    /// another one, for `#[debug(flatten)]`. Returns whether any field was
+    /// left out.
+    #[diagnostic::on_unimplemented(
+        message = "`debug(flatten)` expected a struct with named fields deriving `CustomDebug`, found `{Self}`",
+        label = "cannot be flattened"
+    )]
+    pub trait Flatten {
+        fn debug_fields(&self, debug: &mut DebugStruct<'_, '_>) -> bool;
     }
 
-    fn add_impl_generics_bounds(
-        mut generics: syn::Generics,
-        phantom_data_idents: &Vec<syn::Ident>,
-        associated_types: &Vec<syn::TypePath>,
-        predicates: &Vec<Option<syn::WherePredicate>>,
-    ) -> syn::Generics {
-        let associated_types_idents = associated_types
-            .iter()
-            .map(|ty| &ty.path.segments[0].ident)
-            .collect::<Vec<_>>();
-
-        if predicates.is_empty() {
-            for param in &mut generics.params {
-                if let syn::GenericParam::Type(ref mut type_param) = *param {
-                    if phantom_data_idents.contains(&type_param.ident) {
-                        continue;
-                    }
-
-                    if associated_types_idents.contains(&&type_param.ident) {
-                        continue;
-                    }
-
-                    type_param.bounds.push(syn::parse_quote!(std::fmt::Debug));
-                }
-            }
-
-            let where_clause = generics.make_where_clause();
-            for associated_type in associated_types {
-                where_clause
-                    .predicates
-                    .push(syn::parse_quote!(#associated_type : ::std::fmt::Debug))
-            }
-        } else {
-            let predicate = predicates.first().unwrap();
-
-            if let Some(bound) = predicate {
-                let where_clause = generics.make_where_clause();
-                where_clause.predicates.push(bound.clone());
-            }
+    impl<T: Flatten + ?Sized> Flatten for &T {
+        fn debug_fields(&self, debug: &mut DebugStruct<'_, '_>) -> bool {
+            (**self).debug_fields(debug)
         }
-
-        generics
     }
 
-    fn debug_attribute(attr: &syn::Attribute) -> Option<String> {
-        if attr.path().is_ident("debug") {
-            if let Some(syn::MetaNameValue { path, value, .. }) =
-                attr.parse_args::<syn::MetaNameValue>().ok()
-            {
-                if path.is_ident("bound") {
-                    let result = match value {
-                        syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Str(lit_str),
-                            ..
-                        }) => Ok(lit_str.value()),
-                        _ => Err(syn::Error::new(
-                            value.span(),
-                            "value of debug is not string",
-                        )),
-                    };
-
-                    return result.ok();
-                };
-            }
+    /// SipHash-2-4 of `bytes` under `key`, cut to 32 bits, for
+    /// `#[debug(redact = "fingerprint")]`. Unlike `Hash`, the bytes do not
+    /// depend on the target, so equal values print equal fingerprints
+    /// everywhere, and without the key a fingerprint cannot be checked
+    /// against guesses of the secret.
+    pub fn fingerprint(key: &[u8; 16], bytes: &[u8]) -> u32 {
+        let k0 = u64::from_le_bytes([
+            key[0], key[1], key[2], key[3], key[4], key[5], key[6], key[7],
+        ]);
+        let k1 = u64::from_le_bytes([
+            key[8], key[9], key[10], key[11], key[12], key[13], key[14], key[15],
+        ]);
+
+        let mut v = [
+            k0 ^ 0x736f6d6570736575,
+            k1 ^ 0x646f72616e646f6d,
+            k0 ^ 0x6c7967656e657261,
+            k1 ^ 0x7465646279746573,
+        ];
+
+        let compress = |v: &mut [u64; 4], word: u64| {
+            v[3] ^= word;
+            sip_round(v);
+            sip_round(v);
+            v[0] ^= word;
+        };
+
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            compress(&mut v, u64::from_le_bytes(word));
         }
 
-        None
-    }
+        let rest = chunks.remainder();
+        let mut last = [0; 8];
+        last[..rest.len()].copy_from_slice(rest);
+        last[7] = bytes.len() as u8;
+        compress(&mut v, u64::from_le_bytes(last));
 
-    let phantom_data_inner_types = named
-        .iter()
-        .filter_map(|field: &syn::Field| {
-            let inner_ty = inner_ty(&field.ty, Some("PhantomData"));
-
-            if inner_ty.is_some() {
-                return Some(inner_ty.unwrap());
-            }
-
-            None
-        })
-        .collect::<Vec<syn::Type>>();
-
-    let phantom_data_idents = phantom_data_inner_types
-        .iter()
-        .filter_map(|ty: &syn::Type| {
-            if let syn::Type::Path(ref ty_path) = ty {
-                if ty_path.path.segments.len() == 1 {
-                    return Some(ty_path.path.segments[0].ident.clone());
-                }
-            }
-
-            None
-        })
-        .collect::<Vec<_>>();
-
-    let generic_types = &ast
-        .generics
-        .type_params()
-        .map(|t| &t.ident)
-        .collect::<Vec<&syn::Ident>>();
-
-    let associated_types = named
-        .iter()
-        .filter_map(|field: &syn::Field| associated_ty(&field.ty, &generic_types))
-        .collect::<Vec<syn::TypePath>>();
-
-    let predicates = ast
-        .attrs
-        .iter()
-        .map(|attr: &syn::Attribute| {
-            if let Some(string) = debug_attribute(&attr) {
-                return syn::parse_str::<syn::WherePredicate>(&string).ok();
-            }
-
-            None
-        })
-        .collect::<Vec<_>>();
-
-    let generics = add_impl_generics_bounds(
-        ast.generics,
-        &phantom_data_idents,
-        &associated_types,
-        &predicates,
-    );
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    let quote = quote::quote! {
-         impl #impl_generics std::fmt::Debug for #struct_ident #ty_generics #where_clause {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.debug_struct(stringify!(#struct_ident))
-                    #(.field(
-                        stringify!(#struct_fields_names),
-                        &format_args!(#formatter_fields_names, &self.#struct_fields_names)
-                    ))*
-                    .finish()
-            }
+        v[2] ^= 0xff;
+        for _ in 0..4 {
+            sip_round(&mut v);
         }
-    };
 
-    proc_macro::TokenStream::from(quote)
+        ((v[0] ^ v[1] ^ v[2] ^ v[3]) >> 32) as u32
+    }
+
+    fn sip_round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    /// Entries of a collection ordered by `key`, for `#[debug(sorted)]`.
+    /// Entries with equal keys are all kept, in iteration order.
+    #[cfg(feature = "alloc")]
+    pub fn sorted<I, K>(entries: I, key: impl Fn(&I::Item) -> &K) -> alloc::vec::Vec<I::Item>
+    where
+        I: IntoIterator,
+        K: Ord + ?Sized,
+    {
+        let mut entries: alloc::vec::Vec<I::Item> = entries.into_iter().collect();
+        entries.sort_by(|a, b| Ord::cmp(key(a), key(b)));
+        entries
+    }
 }
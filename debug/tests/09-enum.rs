@@ -0,0 +1,57 @@
+// Generate Debug for enums as well. Each variant is printed the way the
+// standard derive would print it: unit variants by name, tuple variants with
+// debug_tuple and struct variants with debug_struct.
+//
+//     impl<T: Debug> Debug for Message<T> {
+//         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//             match self {
+//                 Self::Quit {} => f.write_str("Quit"),
+//                 Self::Write { 0: __self_0 } => f
+//                     .debug_tuple("Write")
+//                     .field(&format_args!("{:?}", __self_0))
+//                     .finish(),
+//                 ...
+//             }
+//         }
+//     }
+//
+// The #[debug = "..."] attribute applies to variant fields the same way it
+// does to struct fields, and bounds are inferred from the fields of all
+// variants together.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub enum Message<T, U> {
+    Quit,
+    Write(T, #[debug = "0x{:02x}"] u8),
+    Move {
+        x: i32,
+        #[debug = "{}px"]
+        y: i32,
+    },
+    Marker(PhantomData<U>),
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    assert_debug::<Message<u8, NotDebug>>();
+    assert_debug::<Never>();
+
+    let quit = Message::<&str, ()>::Quit;
+    assert_eq!(format!("{:?}", quit), "Quit");
+
+    let write = Message::<&str, ()>::Write("hello", 10);
+    assert_eq!(format!("{:?}", write), r#"Write("hello", 0x0a)"#);
+
+    let moved = Message::<&str, ()>::Move { x: 1, y: 2 };
+    assert_eq!(format!("{:?}", moved), "Move { x: 1, y: 2px }");
+}
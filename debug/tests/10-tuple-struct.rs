@@ -0,0 +1,28 @@
+// Tuple structs are printed with debug_tuple and unit structs by their bare
+// name, matching the standard derive. Positional fields accept the same
+// #[debug = "..."] attribute as named ones.
+//
+//     impl Debug for Handle {
+//         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//             f.debug_tuple("Handle")
+//                 .field(&format_args!("{:#x}", &self.0))
+//                 .finish()
+//         }
+//     }
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Handle(#[debug = "{:#x}"] u32);
+
+#[derive(CustomDebug)]
+pub struct Pair<T>(T, &'static str);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+fn main() {
+    assert_eq!(format!("{:?}", Handle(255)), "Handle(0xff)");
+    assert_eq!(format!("{:?}", Pair(1, "one")), r#"Pair(1, "one")"#);
+    assert_eq!(format!("{:?}", Marker), "Marker");
+}
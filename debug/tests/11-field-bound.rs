@@ -0,0 +1,51 @@
+// The escape hatch is also accepted on individual fields. There it replaces
+// only the bounds that would have been inferred from that field's type, while
+// bounds are still inferred from the other fields:
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug, but its associated type does.
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+
+    let wrapper = Wrapper::<Id, &str> {
+        field: Field { values: vec![1, 2] },
+        normal: "normal",
+    };
+
+    let debug = format!("{:?}", wrapper);
+    let expected = r#"Wrapper { field: Field { values: [1, 2] }, normal: "normal" }"#;
+
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,40 @@
+// Hand-written bounds may be split across several attributes, and a single
+// attribute may list several comma-separated predicates. All of them end up
+// in the where-clause of the generated impl:
+//
+//     impl<T: Trait, U: Trait> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U::Value: Debug,
+//         U::Extra: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+    type Extra;
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug")]
+#[debug(bound = "U::Value: Debug, U::Extra: Debug")]
+pub struct Wrapper<T: Trait, U: Trait> {
+    first: Vec<T::Value>,
+    second: (Vec<U::Value>, Option<U::Extra>),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug, but its associated types do.
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+        type Extra = &'static str;
+    }
+
+    assert_debug::<Wrapper<Id, Id>>();
+}
@@ -0,0 +1,16 @@
+// Bounds that do not parse as where-predicates must not be silently ignored.
+// The error should point at the string literal containing the bound.
+
+use derive_debug::CustomDebug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug,, T: Trait")]
+pub struct Wrapper<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn main() {}
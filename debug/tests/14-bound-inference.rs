@@ -0,0 +1,51 @@
+// Bound inference looks through the whole type of every field rather than
+// just its outermost generic argument. Type parameters get a `T: Debug` bound
+// wherever they are used, whether behind references, in arrays and tuples or
+// in any generic argument position, and associated types get their own bound
+// whether written as `T::Value` or `<T as Trait>::Value`.
+//
+// Types that implement Debug regardless of their parameters do not produce
+// bounds: PhantomData under any path, including wrappers like
+// `PhantomData<fn() -> T>`, as well as fn pointers and raw pointers.
+//
+//     impl<'a, T: Trait, U, V, W> Debug for Field<'a, T, U, V, W>
+//     where
+//         T::Value: Debug,
+//         <T as Trait>::Extra: Debug,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+    type Extra;
+}
+
+#[derive(CustomDebug)]
+pub struct Field<'a, T: Trait, U, V, W> {
+    values: (Vec<T::Value>, [Option<<T as Trait>::Extra>; 2]),
+    results: Result<&'a U, String>,
+    marker: std::marker::PhantomData<V>,
+    callback: PhantomData<fn() -> W>,
+    pointer: *const W,
+    function: fn(W) -> V,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug, but its associated types do.
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+        type Extra = &'static str;
+    }
+
+    struct NotDebug;
+
+    assert_debug::<Field<Id, u8, NotDebug, NotDebug>>();
+}
@@ -0,0 +1,66 @@
+// Fields such as caches or raw buffers can be left out of the output with
+// #[debug(skip)]. A skipped field does not take part in bound inference, so
+// its type does not need to implement Debug.
+//
+// With #[debug(skip_if = "path")] the field is left out only when the given
+// function returns true for it, for example to hide empty options:
+//
+//     #[debug(skip_if = "Option::is_none")]
+//     parent: Option<u32>,
+//
+// Whenever a field was left out, the output ends in `..` the way
+// finish_non_exhaustive() prints it, so readers can tell it is partial.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Cache;
+
+#[derive(CustomDebug)]
+pub struct Node<T> {
+    id: u32,
+    #[debug(skip)]
+    cache: T,
+    #[debug(skip_if = "Option::is_none")]
+    parent: Option<u32>,
+}
+
+#[derive(CustomDebug)]
+pub struct Entry {
+    name: &'static str,
+    #[debug(skip_if = "Vec::is_empty")]
+    children: Vec<u32>,
+}
+
+#[derive(CustomDebug)]
+pub enum Buffer {
+    Raw(u32, #[debug(skip)] Vec<u8>),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Node<Cache>>();
+
+    let node = Node {
+        id: 1,
+        cache: Cache,
+        parent: Some(0),
+    };
+    assert_eq!(format!("{:?}", node), "Node { id: 1, parent: Some(0), .. }");
+
+    let entry = Entry {
+        name: "root",
+        children: vec![],
+    };
+    assert_eq!(format!("{:?}", entry), r#"Entry { name: "root", .. }"#);
+
+    let entry = Entry {
+        name: "root",
+        children: vec![1],
+    };
+    assert_eq!(format!("{:?}", entry), r#"Entry { name: "root", children: [1] }"#);
+
+    let buffer = Buffer::Raw(3, vec![1, 2, 3]);
+    assert_eq!(format!("{:?}", buffer), "Raw(3, ..)");
+}
@@ -0,0 +1,67 @@
+// Some values cannot be expressed with a format string, such as a Duration
+// printed in milliseconds or a map printed in sorted order. For these a field
+// may name a formatting function with #[debug(with = "path")]:
+//
+//     fn fmt_duration(value: &Duration, f: &mut fmt::Formatter) -> fmt::Result
+//
+// The function is called through a generated adapter implementing Debug, so
+// the output composes with debug_struct and the field type does not need to
+// implement Debug itself.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::time::Duration;
+
+mod formatters {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::time::Duration;
+
+    pub fn fmt_duration(value: &Duration, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}ms", value.as_millis())
+    }
+
+    pub fn fmt_sorted(value: &HashMap<&str, u32>, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = value.iter().collect::<Vec<_>>();
+        entries.sort();
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+pub struct Opaque;
+
+fn fmt_opaque<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("<opaque>")
+}
+
+#[derive(CustomDebug)]
+pub struct Request<T> {
+    #[debug(with = "formatters::fmt_duration")]
+    timeout: Duration,
+    #[debug(with = "formatters::fmt_sorted")]
+    headers: HashMap<&'static str, u32>,
+    #[debug(with = "fmt_opaque")]
+    body: T,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Request<Opaque>>();
+
+    let mut headers = HashMap::new();
+    headers.insert("b", 2);
+    headers.insert("a", 1);
+
+    let request = Request {
+        timeout: Duration::from_secs(2),
+        headers,
+        body: Opaque,
+    };
+
+    let debug = format!("{:?}", request);
+    let expected = r#"Request { timeout: 2000ms, headers: {"a": 1, "b": 2}, body: <opaque> }"#;
+
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,84 @@
+// Secrets such as tokens and passwords must never reach the logs. A field
+// marked #[debug(redact)] prints as <redacted> instead of its value, and its
+// type is left out of bound inference.
+//
+// Two variants leak a little more information to help debugging:
+//
+//   - #[debug(redact = "len")] prints only the length, for fields with a
+//     len() method.
+//
+//   - #[debug(redact = "fingerprint", key = "KEY")] prints a short hash of
+//     the bytes of an AsRef<[u8]> field, enough to tell whether two values
+//     are equal. The hash is keyed by KEY, a secret [u8; 16] the user
+//     provides, so that it cannot be checked against guesses of the value.
+//     Fingerprints are the same on every target for the same key.
+//
+// On the container, #[debug(redact_all_except(...))] redacts every field
+// except the ones listed.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Secret;
+
+static KEY: [u8; 16] = *b"0123456789abcdef";
+static OTHER_KEY: [u8; 16] = *b"fedcba9876543210";
+
+#[derive(CustomDebug)]
+pub struct Credentials<S> {
+    user: &'static str,
+    #[debug(redact)]
+    secret: S,
+    #[debug(redact = "len")]
+    password: String,
+}
+
+#[derive(CustomDebug)]
+pub struct Token(#[debug(redact = "fingerprint", key = "KEY")] &'static str);
+
+#[derive(CustomDebug)]
+pub struct OtherToken(#[debug(key = "OTHER_KEY", redact = "fingerprint")] Vec<u8>);
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except(id))]
+pub struct Session {
+    id: u32,
+    cookie: String,
+    #[debug(redact = "len")]
+    key: Vec<u8>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Credentials<Secret>>();
+
+    let credentials = Credentials {
+        user: "root",
+        secret: Secret,
+        password: "hunter2".to_owned(),
+    };
+
+    let debug = format!("{:?}", credentials);
+    let expected = r#"Credentials { user: "root", secret: <redacted>, password: <redacted len=7> }"#;
+
+    assert_eq!(debug, expected);
+
+    let token = format!("{:?}", Token("abc"));
+    assert_eq!(token, "Token(<redacted #0d8e287e>)");
+    assert_ne!(token, format!("{:?}", Token("abd")));
+
+    let other = format!("{:?}", OtherToken(b"abc".to_vec()));
+    assert_ne!(other["Other".len()..], token);
+
+    let session = Session {
+        id: 7,
+        cookie: "session=1234".to_owned(),
+        key: vec![0; 16],
+    };
+
+    let debug = format!("{:?}", session);
+    let expected = "Session { id: 7, cookie: <redacted>, key: <redacted len=16> }";
+
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,39 @@
+// The printed names default to the Rust identifiers, which can leak internal
+// naming into user-facing diagnostics. Types, variants and fields accept
+// #[debug(rename = "...")] to print a different name instead.
+//
+// Raw identifiers are printed without their `r#` prefix, so a field declared
+// as `r#type` prints as `type`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "Request")]
+pub struct HttpRequestInternal {
+    r#type: &'static str,
+    #[debug(rename = "url")]
+    raw_url: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    #[debug(rename = "Click")]
+    MouseClick { x: i32, y: i32 },
+    r#Move(i32),
+}
+
+fn main() {
+    let request = HttpRequestInternal {
+        r#type: "GET",
+        raw_url: "/",
+    };
+
+    let debug = format!("{:?}", request);
+    let expected = r#"Request { type: "GET", url: "/" }"#;
+
+    assert_eq!(debug, expected);
+
+    let click = Event::MouseClick { x: 1, y: 2 };
+    assert_eq!(format!("{:?}", click), "Click { x: 1, y: 2 }");
+    assert_eq!(format!("{:?}", Event::r#Move(3)), "Move(3)");
+}
@@ -0,0 +1,56 @@
+// Wrapper types like `struct UserId(u64)` are most readable printed as just
+// their inner value. With #[debug(transparent)] on a struct with a single
+// field, the struct is formatted exactly as that field.
+//
+// In the other direction, a field marked #[debug(flatten)] has its own fields
+// inlined into the outer debug_struct instead of being printed as a nested
+// struct. This works with any named-field struct that also derives
+// CustomDebug, which implements a hidden trait adding its fields to another
+// struct's output.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Name {
+    #[debug = "<{}>"]
+    value: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Timestamps {
+    created: u32,
+    #[debug(skip)]
+    cached: bool,
+}
+
+#[derive(CustomDebug)]
+pub struct User {
+    id: UserId,
+    name: Name,
+    #[debug(flatten)]
+    timestamps: Timestamps,
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(7)), "7");
+    assert_eq!(format!("{:#?}", UserId(7)), "7");
+
+    let user = User {
+        id: UserId(7),
+        name: Name { value: "root" },
+        timestamps: Timestamps {
+            created: 100,
+            cached: true,
+        },
+    };
+
+    let debug = format!("{:?}", user);
+    let expected = "User { id: 7, name: <root>, created: 100, .. }";
+
+    assert_eq!(debug, expected);
+}
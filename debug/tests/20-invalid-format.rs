@@ -0,0 +1,23 @@
+// Format strings are checked when the derive expands rather than inside the
+// generated `format_args!`, so each error points at the offending literal or
+// attribute instead of at the derive.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug = "0b{:08q}"]
+    unknown_trait: u8,
+    #[debug = "{} {}"]
+    two_arguments: u8,
+    #[debug = "{value}"]
+    named_argument: u8,
+    #[debug = "{:x"]
+    unterminated: u8,
+    #[debug = "no placeholder"]
+    no_placeholder: u8,
+    #[debug]
+    path: u8,
+}
+
+fn main() {}
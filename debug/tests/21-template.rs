@@ -0,0 +1,89 @@
+// Some values only make sense read together, such as a bitmask next to its
+// width. A format template can refer to other fields, either as arguments
+// given to #[debug(fmt = "...", args(...))] or inline as `{self.field}`.
+//
+// On a field the template replaces the printed value, while on the container
+// it replaces the whole output.
+//
+// Type parameters are bounded by the traits the template prints the fields
+// through, such as Display for `{self.num}`, rather than by Debug.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+#[derive(CustomDebug)]
+pub struct Mask {
+    #[debug(fmt = "{:#b} ({} bits)", args(self.bits, self.width))]
+    bits: u8,
+    width: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{self.num}/{self.den}")]
+pub struct Ratio {
+    num: i32,
+    den: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "Point{{{}, {self.1}}}", args(self.0 * 2))]
+pub struct Point(i32, i32);
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{self.num}/{self.den}")]
+pub struct GenericRatio<T> {
+    num: T,
+    den: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Register<A, N> {
+    #[debug(fmt = "{self.name} at {:#x}", args(self.address))]
+    address: A,
+    #[debug(skip)]
+    name: N,
+}
+
+// Display and LowerHex only, which the bounds must not go beyond.
+pub struct Digit(u8);
+
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::LowerHex for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+fn assert_debug<T: Debug>() {}
+
+fn main() {
+    let mask = Mask {
+        bits: 0b101,
+        width: 3,
+    };
+    assert_eq!(format!("{:?}", mask), "Mask { bits: 0b101 (3 bits), width: 3 }");
+
+    let ratio = Ratio { num: 3, den: 4 };
+    assert_eq!(format!("{:?}", ratio), "3/4");
+
+    assert_eq!(format!("{:?}", Point(1, 2)), "Point{2, 2}");
+
+    assert_debug::<GenericRatio<Digit>>();
+    let ratio = GenericRatio {
+        num: Digit(1),
+        den: Digit(2),
+    };
+    assert_eq!(format!("{:?}", ratio), "1/2");
+
+    assert_debug::<Register<Digit, Digit>>();
+    let register = Register {
+        address: Digit(255),
+        name: Digit(4),
+    };
+    assert_eq!(format!("{:?}", register), "Register { address: 4 at 0xff, .. }");
+}
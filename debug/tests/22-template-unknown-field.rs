@@ -0,0 +1,28 @@
+// Templates are checked against the struct's fields when the derive expands,
+// so that a misspelled field is reported at the attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{self.num}/{self.denominator}")]
+pub struct Ratio {
+    num: i32,
+    den: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct Mask {
+    #[debug(fmt = "{:#b} ({} bits)", args(self.bits, self.with))]
+    bits: u8,
+    width: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Arguments {
+    #[debug(fmt = "{} {}", args(self.a))]
+    a: u8,
+    #[debug(fmt = "{}", args(self.a, self.b))]
+    b: u8,
+}
+
+fn main() {}
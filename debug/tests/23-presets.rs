@@ -0,0 +1,90 @@
+// Common formats are available as presets instead of hand-written format
+// strings: #[debug(hex)], #[debug(binary)], #[debug(bytes)] for a hex dump of
+// byte buffers, #[debug(display)] to print through `Display`, and
+// #[debug(truncate = N)] to cut long output after N characters.
+//
+// Each preset stays on one line with {:?}. Under {:#?}, hex and binary are
+// zero-padded to the width of their type and bytes are dumped 16 per line.
+//
+// Instead of Debug, a generic field requires the trait its preset prints
+// through, such as `T: LowerHex` for #[debug(hex)]. Since bytes applies to
+// the buffer as a whole, a `Vec<T>` field requires `Vec<T>: AsRef<[u8]>`.
+
+use derive_debug::CustomDebug;
+use std::fmt::{Display, LowerHex};
+use std::net::Ipv4Addr;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(hex)]
+    id: u16,
+    #[debug(binary)]
+    flags: u8,
+    #[debug(bytes)]
+    payload: Vec<u8>,
+    #[debug(bytes)]
+    checksum: [u8; 2],
+    #[debug(display)]
+    source: Ipv4Addr,
+    #[debug(truncate = 8)]
+    note: String,
+}
+
+#[derive(CustomDebug)]
+pub struct Generic<T, D, B> {
+    #[debug(hex)]
+    value: T,
+    #[debug(display)]
+    label: D,
+    #[debug(bytes)]
+    raw: B,
+}
+
+#[derive(CustomDebug)]
+pub struct Buffer<T> {
+    #[debug(bytes)]
+    data: Vec<T>,
+}
+
+fn generic<T: LowerHex, D: Display, B: AsRef<[u8]>>(value: T, label: D, raw: B) -> String {
+    format!("{:?}", Generic { value, label, raw })
+}
+
+fn main() {
+    let packet = Packet {
+        id: 0x1f,
+        flags: 0b101,
+        payload: (0..18).collect(),
+        checksum: [0xbe, 0xef],
+        source: Ipv4Addr::new(10, 0, 0, 1),
+        note: String::from("a long note"),
+    };
+
+    let debug = format!("{:?}", packet);
+    let expected = concat!(
+        "Packet { id: 0x1f, flags: 0b101, ",
+        "payload: [00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f 10 11], ",
+        "checksum: [be ef], source: 10.0.0.1, note: \"a long ... }",
+    );
+    assert_eq!(debug, expected);
+
+    let debug = format!("{:#?}", packet);
+    let expected = r#"Packet {
+    id: 0x001f,
+    flags: 0b00000101,
+    payload: 00000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f
+    00000010: 10 11,
+    checksum: 00000000: be ef,
+    source: 10.0.0.1,
+    note: "a long ...,
+}"#;
+    assert_eq!(debug, expected);
+
+    assert_eq!(
+        generic(255u8, Ipv4Addr::LOCALHOST, "ab"),
+        "Generic { value: 0xff, label: 127.0.0.1, raw: [61 62] }",
+    );
+
+    let buffer = Buffer { data: vec![1u8, 2] };
+    assert_eq!(format!("{:?}", buffer), "Buffer { data: [01 02] }");
+}
@@ -0,0 +1,38 @@
+// Closures, trait objects and FFI handles often do not implement Debug.
+// #[debug(opaque)] prints such a field as its type name instead, and the
+// field's type is left out of bound inference.
+
+use derive_debug::CustomDebug;
+use std::ffi::c_void;
+
+pub struct Handle;
+
+#[derive(CustomDebug)]
+pub struct Callbacks<T> {
+    name: &'static str,
+    #[debug(opaque)]
+    on_event: Box<dyn Fn(u32) -> bool + Send>,
+    #[debug(opaque)]
+    raw: *mut c_void,
+    #[debug(opaque)]
+    handles: Vec<(T, [u8; 4])>,
+}
+
+fn main() {
+    let callbacks = Callbacks {
+        name: "events",
+        on_event: Box::new(|_| true),
+        raw: std::ptr::null_mut(),
+        handles: vec![(Handle, [0; 4])],
+    };
+
+    let debug = format!("{:?}", callbacks);
+    let expected = concat!(
+        r#"Callbacks { name: "events", "#,
+        "on_event: <Box<dyn Fn(u32) -> bool + Send>>, ",
+        "raw: <*mut c_void>, ",
+        "handles: <Vec<(T, [u8; 4])>> }",
+    );
+
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,61 @@
+// A companion derive for Display, driven by a #[display("...")] template on
+// the struct or on each variant of an enum. Placeholders name the fields,
+// `{field}` or `{0}` for tuple fields, and accept the usual format specs.
+//
+// Bounds are inferred as for CustomDebug, but with the trait each placeholder
+// uses: `{value}` requires `T: Display` while `{value:?}` requires `T: Debug`.
+
+use derive_debug::CustomDisplay;
+use std::fmt::Debug;
+
+#[derive(CustomDisplay)]
+#[display("{name} at {line}:{column}")]
+pub struct Location {
+    name: &'static str,
+    line: u32,
+    column: u32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{{{0:#x}, {1:?}}}")]
+pub struct Pair<T, U>(T, U);
+
+#[derive(CustomDisplay)]
+pub enum Event<T> {
+    #[display("click at ({x}, {y})")]
+    Click { x: i32, y: i32 },
+    #[display("key {0}")]
+    Key(char),
+    #[display("custom {0:?}")]
+    Custom(T),
+    #[display("quit")]
+    Quit,
+}
+
+pub struct NotDisplay;
+
+fn assert_display<T: std::fmt::Display>() {}
+
+fn main() {
+    let location = Location {
+        name: "lib.rs",
+        line: 3,
+        column: 14,
+    };
+    assert_eq!(location.to_string(), "lib.rs at 3:14");
+
+    assert_eq!(Pair(255, "a").to_string(), r#"{0xff, "a"}"#);
+
+    let click: Event<()> = Event::Click { x: 1, y: 2 };
+    assert_eq!(click.to_string(), "click at (1, 2)");
+    assert_eq!(Event::<()>::Key('q').to_string(), "key q");
+    assert_eq!(Event::Custom(vec![1]).to_string(), "custom [1]");
+    assert_eq!(Event::<()>::Quit.to_string(), "quit");
+
+    // `T` only needs `Debug` here, so any `T: Debug` works.
+    fn custom<T: Debug>(value: T) -> String {
+        Event::Custom(value).to_string()
+    }
+    assert_eq!(custom("x"), r#"custom "x""#);
+    assert_display::<Event<Vec<u8>>>();
+}
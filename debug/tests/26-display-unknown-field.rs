@@ -0,0 +1,24 @@
+// Display templates are checked against the fields when the derive expands,
+// and every struct or variant needs one.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} at {lnie}")]
+pub struct Location {
+    name: &'static str,
+    line: u32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{} {}")]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDisplay)]
+pub enum Event {
+    #[display("quit")]
+    Quit,
+    Resize(u32, u32),
+}
+
+fn main() {}
@@ -0,0 +1,56 @@
+// Const generic parameters and lifetimes need no bounds of their own: an
+// array `[T; N]` only requires `T: Debug`, and references only require
+// `Debug` for what they point to.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Buffer<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+#[derive(CustomDebug)]
+pub struct Borrowed<'a, 'b: 'a, T: ?Sized> {
+    name: &'a str,
+    value: &'b T,
+    nested: Option<&'a [&'b str]>,
+}
+
+#[derive(CustomDebug)]
+pub enum Chunk<'a, const N: usize> {
+    Inline([u8; N]),
+    Borrowed(&'a [u8]),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Any `T: Debug` and any `N` work, without bounds on `N`.
+    fn buffer<T: Debug, const N: usize>() {
+        assert_debug::<Buffer<T, N>>();
+    }
+    buffer::<u8, 0>();
+    buffer::<String, 64>();
+
+    let buffer = Buffer {
+        items: [1, 2],
+        len: 2,
+    };
+    assert_eq!(format!("{:?}", buffer), "Buffer { items: [1, 2], len: 2 }");
+
+    let value = String::from("value");
+    let borrowed = Borrowed {
+        name: "name",
+        value: value.as_str(),
+        nested: Some(&["a"]),
+    };
+    assert_eq!(
+        format!("{:?}", borrowed),
+        r#"Borrowed { name: "name", value: "value", nested: Some(["a"]) }"#,
+    );
+
+    assert_eq!(format!("{:?}", Chunk::<2>::Inline([1, 2])), "Inline([1, 2])");
+    assert_eq!(format!("{:?}", Chunk::<2>::Borrowed(&[3])), "Borrowed([3])");
+}
@@ -0,0 +1,29 @@
+// Which field of a union is initialized cannot be known, so a union prints
+// as its name alone, `Bits { .. }`, and its fields need not implement Debug.
+
+use derive_debug::CustomDebug;
+
+#[derive(Clone, Copy)]
+pub struct NotDebug;
+
+#[derive(CustomDebug)]
+pub union Bits<T: Copy> {
+    int: u32,
+    float: f32,
+    other: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename = "Word")]
+pub union RawWord {
+    bytes: [u8; 4],
+    value: u32,
+}
+
+fn main() {
+    let bits: Bits<NotDebug> = Bits { int: 1 };
+    assert_eq!(format!("{:?}", bits), "Bits { .. }");
+
+    let word = RawWord { value: 1 };
+    assert_eq!(format!("{:?}", word), "Word { .. }");
+}
@@ -0,0 +1,60 @@
+// The generated code only refers to `::core`, so both derives work in
+// #![no_std] crates, where `std` is not in scope. The standard library is
+// linked here under another name to run the test.
+
+#![no_std]
+
+extern crate std as host;
+
+use core::fmt::Debug;
+use derive_debug::{CustomDebug, CustomDisplay};
+use host::format;
+
+static KEY: [u8; 16] = [7; 16];
+
+#[derive(CustomDebug)]
+pub struct Registers<T> {
+    #[debug = "0b{:08b}"]
+    status: u8,
+    #[debug(hex)]
+    address: u32,
+    #[debug(bytes)]
+    data: [u8; 2],
+    #[debug(redact = "fingerprint", key = "KEY")]
+    key: [u8; 4],
+    #[debug(truncate = 4)]
+    label: &'static str,
+    value: T,
+    #[debug(with = "core::fmt::Display::fmt")]
+    code: char,
+    #[debug(fmt = "{self.status}/{}", args(self.address))]
+    summary: (),
+}
+
+#[derive(CustomDisplay)]
+pub enum Level<T: Debug> {
+    #[display("level {0}")]
+    Fixed(u8),
+    #[display("custom {0:?}")]
+    Custom(T),
+}
+
+fn main() {
+    let registers = Registers {
+        status: 1,
+        address: 0xff,
+        data: [0xbe, 0xef],
+        key: [0, 0, 0, 42],
+        label: "register",
+        value: 'x',
+        code: 'y',
+        summary: (),
+    };
+
+    let debug = format!("{:?}", registers);
+    assert!(debug.starts_with("Registers { status: 0b00000001, address: 0xff, data: [be ef], key: <redacted #"));
+    assert!(debug.ends_with(r#"label: "reg..., value: 'x', code: y, summary: 1/255 }"#));
+
+    assert_eq!(format!("{}", Level::<()>::Fixed(3)), "level 3");
+    assert_eq!(format!("{}", Level::Custom(-1)), "custom -1");
+}
@@ -0,0 +1,85 @@
+// Besides Debug, the derive implements derive_debug::Inspect, which passes
+// each printed field to a FieldVisitor under its printed name, for example to
+// build key/value records for structured logging. Fields are formatted as in
+// the Debug output, so skip, rename and redact apply the same way.
+
+use derive_debug::{CustomDebug, FieldVisitor, Inspect};
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Request {
+    #[debug(rename = "url")]
+    raw_url: &'static str,
+    #[debug(redact)]
+    token: &'static str,
+    #[debug(skip)]
+    retries: u32,
+    #[debug(hex)]
+    flags: u8,
+    #[debug(flatten)]
+    origin: Origin,
+}
+
+#[derive(CustomDebug)]
+pub struct Origin {
+    host: &'static str,
+    port: u16,
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Resize(u32, u32),
+    Quit,
+}
+
+#[derive(Default)]
+struct Record(Vec<(String, String)>);
+
+impl FieldVisitor for Record {
+    fn visit_field(&mut self, name: &str, value: &dyn Debug) {
+        self.0.push((name.to_owned(), format!("{:?}", value)));
+    }
+}
+
+fn record(value: &dyn Inspect) -> Vec<(String, String)> {
+    let mut record = Record::default();
+    value.visit(&mut record);
+    record.0
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+        .collect()
+}
+
+fn main() {
+    let request = Request {
+        raw_url: "/",
+        token: "secret",
+        retries: 3,
+        flags: 0x1f,
+        origin: Origin {
+            host: "localhost",
+            port: 80,
+        },
+    };
+
+    assert_eq!(
+        record(&request),
+        pairs(&[
+            ("url", r#""/""#),
+            ("token", "<redacted>"),
+            ("flags", "0x1f"),
+            ("host", r#""localhost""#),
+            ("port", "80"),
+        ]),
+    );
+
+    assert_eq!(
+        record(&Event::Resize(640, 480)),
+        pairs(&[("0", "640"), ("1", "480")]),
+    );
+    assert_eq!(record(&Event::Quit), pairs(&[]));
+}
@@ -0,0 +1,74 @@
+// A format string given with #[debug = "..."] applies to the whole field. For
+// collections, #[debug(each = "...")] and #[debug(each_with = "...")] format
+// every item instead. The field is printed as a list, or as a map or a set when
+// its type is named like one, such as HashMap or BTreeSet. Types named
+// otherwise, such as aliases, are marked with #[debug(map)] or #[debug(set)].
+//
+// The items need not implement Debug. For type parameters, the bounds follow
+// the item format, such as `T: LowerHex` for `each = "{:x}"`, and the keys of a
+// map need to implement Debug.
+
+use derive_debug::CustomDebug;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+pub struct Handle(u32);
+
+fn handle(handle: &Handle, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "#{}", handle.0)
+}
+
+pub type Registry<K> = BTreeMap<K, Handle>;
+
+#[derive(CustomDebug)]
+pub struct Table<'a, K, T> {
+    #[debug(each = "0x{:x}")]
+    addresses: Vec<u32>,
+    #[debug(each_with = "handle")]
+    handles: Vec<Handle>,
+    #[debug(each_with = "handle")]
+    by_name: BTreeMap<&'static str, Handle>,
+    #[debug(each = "{:.1}")]
+    borrowed: &'a [f64],
+    #[debug(each = "{:x}")]
+    masks: BTreeSet<T>,
+    #[debug(map, each_with = "handle")]
+    registry: Registry<K>,
+}
+
+// Neither Debug nor Display, which the bounds must not require.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mask(u8);
+
+impl fmt::LowerHex for Mask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+fn main() {
+    let mut by_name = BTreeMap::new();
+    by_name.insert("a", Handle(1));
+    by_name.insert("b", Handle(2));
+
+    let mut registry = Registry::new();
+    registry.insert(7, Handle(4));
+
+    let table = Table {
+        addresses: vec![16, 255],
+        handles: vec![Handle(3)],
+        by_name,
+        borrowed: &[0.25, 1.0],
+        masks: [Mask(15), Mask(160)].into_iter().collect(),
+        registry,
+    };
+
+    let debug = format!("{:?}", table);
+    let expected = concat!(
+        "Table { addresses: [0x10, 0xff], handles: [#3], ",
+        r#"by_name: {"a": #1, "b": #2}, borrowed: [0.2, 1.0], "#,
+        "masks: {f, a0}, registry: {7: #4} }",
+    );
+
+    assert_eq!(debug, expected);
+}
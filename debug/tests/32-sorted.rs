@@ -0,0 +1,51 @@
+// HashMap and HashSet iterate in an unspecified order, which makes their
+// Debug output unusable in snapshot tests. #[debug(sorted)] prints map and
+// set fields ordered by key instead, which requires the keys to be Ord. It
+// combines with #[debug(each = "...")] to also format the values.
+//
+// Entries with equal keys are all printed, so a Vec printed as a set with
+// #[debug(set, sorted)] keeps its duplicates. Sorting collects the entries,
+// which needs the `alloc` feature of derive_debug, enabled by default.
+
+use derive_debug::CustomDebug;
+use std::collections::{HashMap, HashSet};
+
+#[derive(CustomDebug)]
+pub struct Index<K> {
+    #[debug(sorted)]
+    counts: HashMap<&'static str, u32>,
+    #[debug(sorted)]
+    tags: HashSet<u8>,
+    #[debug(sorted, each = "0x{:02x}")]
+    codes: HashMap<char, u8>,
+    #[debug(sorted)]
+    empty: HashSet<u8>,
+    #[debug(set, sorted)]
+    samples: Vec<u8>,
+    #[debug(sorted)]
+    generic: HashMap<K, u8>,
+}
+
+fn main() {
+    let index = Index {
+        counts: [("pear", 2), ("apple", 5), ("fig", 1), ("kiwi", 3)]
+            .into_iter()
+            .collect(),
+        tags: [9, 3, 200, 0, 42].into_iter().collect(),
+        codes: [('b', 11), ('a', 10), ('c', 12)].into_iter().collect(),
+        empty: HashSet::new(),
+        samples: vec![3, 1, 3, 2, 1],
+        generic: [("y", 2), ("x", 1)].into_iter().collect(),
+    };
+
+    let debug = format!("{:?}", index);
+    let expected = concat!(
+        r#"Index { counts: {"apple": 5, "fig": 1, "kiwi": 3, "pear": 2}, "#,
+        "tags: {0, 3, 9, 42, 200}, ",
+        "codes: {'a': 0x0a, 'b': 0x0b, 'c': 0x0c}, ",
+        "empty: {}, samples: {1, 1, 2, 3, 3}, ",
+        r#"generic: {"x": 1, "y": 2} }"#,
+    );
+
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,23 @@
+// Only structs with named fields that derive CustomDebug can be flattened.
+// Anything else is reported at the type of the flattened field.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Id(u64);
+
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct User {
+    #[debug(flatten)]
+    id: Id,
+    #[debug(flatten)]
+    position: Point,
+}
+
+fn main() {}
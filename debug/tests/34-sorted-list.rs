@@ -0,0 +1,13 @@
+// Only the entries of maps and sets have an order of their own to hide, so
+// #[debug(sorted)] is rejected on other collections. A Vec may still be
+// printed sorted with #[debug(set, sorted)].
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Samples {
+    #[debug(sorted)]
+    values: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// A fingerprint without a key could be checked against guesses of the
+// secret, so #[debug(redact = "fingerprint")] requires `key = "..."`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Token(#[debug(redact = "fingerprint")] String);
+
+fn main() {}
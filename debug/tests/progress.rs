@@ -9,4 +9,31 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.pass("tests/10-tuple-struct.rs");
+    t.pass("tests/11-field-bound.rs");
+    t.pass("tests/12-multiple-bounds.rs");
+    t.compile_fail("tests/13-invalid-bound.rs");
+    t.pass("tests/14-bound-inference.rs");
+    t.pass("tests/15-skip.rs");
+    t.pass("tests/16-with.rs");
+    t.pass("tests/17-redact.rs");
+    t.pass("tests/18-rename.rs");
+    t.pass("tests/19-transparent-flatten.rs");
+    t.compile_fail("tests/20-invalid-format.rs");
+    t.pass("tests/21-template.rs");
+    t.compile_fail("tests/22-template-unknown-field.rs");
+    t.pass("tests/23-presets.rs");
+    t.pass("tests/24-opaque.rs");
+    t.pass("tests/25-display.rs");
+    t.compile_fail("tests/26-display-unknown-field.rs");
+    t.pass("tests/27-const-generics-lifetimes.rs");
+    t.pass("tests/28-union.rs");
+    t.pass("tests/29-no-std.rs");
+    t.pass("tests/30-inspect.rs");
+    t.pass("tests/31-each.rs");
+    t.pass("tests/32-sorted.rs");
+    t.compile_fail("tests/33-flatten-unsupported.rs");
+    t.compile_fail("tests/34-sorted-list.rs");
+    t.compile_fail("tests/35-fingerprint-key.rs");
 }